@@ -2,3 +2,16 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Install(pub String);
+
+impl Install {
+    /// Install block putting every binary in the formula's `bin` directory
+    pub fn from_binaries(binaries: &[String]) -> Install {
+        Install(
+            binaries
+                .iter()
+                .map(|binary| format!("bin.install \"{}\"", binary))
+                .collect::<Vec<String>>()
+                .join("\n        "),
+        )
+    }
+}
@@ -5,23 +5,23 @@ pub mod target;
 
 use self::{
     install::Install,
-    package::Package,
+    package::{Package, Packages},
     repository::Repository,
     target::{MultiTarget, SingleTarget, Target, Targets},
 };
 use crate::{
-    build::{arch::Arch, committer::Committer},
-    config::{BrewConfig, CommitterConfig, PullRequestConfig, ReleaseConfig},
-    git,
+    build::{arch::Arch, committer::Committer, compression::Compression, os::Os, Build},
+    config::{BrewConfig, CommitterConfig, PullRequestConfig},
     github::{builder::BuilderExecutor, github_client, tag::Tag},
-    http,
     template::{handlebars, Template},
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use sha2::Digest;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Brew {
@@ -47,35 +47,23 @@ pub struct Brew {
 }
 
 impl Brew {
-    pub async fn new(
-        brew: BrewConfig,
-        release_config: ReleaseConfig,
-        version: Tag,
-        packages: Vec<Package>,
-    ) -> Result<Brew> {
-        let url = format!(
-            "https://github.com/{}/{}/archive/refs/tags/v{}.tar.gz",
-            release_config.owner, release_config.repo, version.name
-        );
-        let hash = {
-            let mut hasher = sha2::Sha256::new();
-            let bytes = http::HttpClient::new()
-                .get(&url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
-            hasher.update(&bytes);
-            format!("{:x}", hasher.finalize())
-        };
-        Ok(Brew {
+    /// The formula of `packages`, whose source tarball, when there is one, is
+    /// the `url` of the formula
+    pub fn new(brew: BrewConfig, version: Tag, packages: Packages, binaries: Vec<String>) -> Brew {
+        let (url, hash) = packages
+            .source
+            .map(|source| (source.url.unwrap_or_default(), source.sha256))
+            .unwrap_or_default();
+        Brew {
             name: captalize(brew.name),
             description: brew.description,
             homepage: brew.homepage,
-            install_info: brew.install,
+            install_info: brew
+                .install
+                .unwrap_or_else(|| Install::from_binaries(&binaries)),
             repository: brew.repository,
             tag: version,
-            targets: Targets::from(packages),
+            targets: Targets::from(packages.archives),
             license: brew.license,
             head: brew.head,
             test: brew.test,
@@ -86,7 +74,7 @@ impl Brew {
             path: brew.path,
             url,
             hash,
-        })
+        }
     }
 }
 
@@ -99,20 +87,34 @@ pub struct BrewArch {
 
 pub async fn release(
     brew_config: BrewConfig,
-    release_config: ReleaseConfig,
-    packages: Vec<Package>,
-    template: Template,
-    base: PathBuf,
+    packages: Packages,
+    build_info: &Build,
+    tag: Tag,
     dry_run: bool,
-    output_path: &PathBuf,
+    output_path: &Path,
 ) -> Result<String> {
-    let brew = Brew::new(
-        brew_config,
-        release_config,
-        git::get_current_tag(&base)?,
-        packages,
-    )
-    .await?;
+    if build_info.splits_binaries()? {
+        bail!(anyhow::anyhow!(
+            "a formula needs every binary in a single archive, disable `archive.split` and use an archive format"
+        ));
+    }
+
+    // a raw binary is downloaded under its asset name, which the default
+    // install block does not know
+    let raw = build_info.archive_targets()?.iter().any(|target| {
+        !target.os.is_windows() && matches!(build_info.compression(target), Compression::Binary)
+    });
+    if raw && brew_config.install.is_none() {
+        bail!("a formula cannot install raw binaries, use an archive `compression` or set `brew.install`");
+    }
+
+    if brew_config.name.trim().is_empty() {
+        bail!("no formula name found in the package metadata, set `brew.name`");
+    }
+
+    let brew = Brew::new(brew_config, tag, packages, build_info.binaries.names());
+
+    let template = Template::from(build_info.to_owned());
 
     log::debug!("Rendering Formula template {}", template.to_string());
     let data = serialize_brew(&brew, template)?;
@@ -129,12 +131,12 @@ pub async fn release(
                 .repo(&brew.repository.owner, &brew.repository.name)
                 .branch(&brew.head)
                 .upsert_file()
-                .path(if brew.path.is_some() {
-                    format!("{}/{}.rb", brew.path.unwrap(), brew.name)
+                .path(if let Some(path) = &brew.path {
+                    format!("{}/{}.rb", path, brew.name)
                 } else {
                     format!("{}.rb", brew.name)
                 })
-                .message(brew.commit_message.replace("{{version}}", &brew.tag.name))
+                .message(brew.commit_message.replace("{{version}}", brew.tag.value()))
                 .content(&data)
                 .execute()
                 .await
@@ -199,7 +201,7 @@ async fn push_formula(brew: Brew) -> Result<()> {
         .branch(&pull_request.head)
         .upsert_file()
         .path(format!("{}.rb", brew.name))
-        .message(brew.commit_message.replace("{{version}}", &brew.tag.name))
+        .message(brew.commit_message.replace("{{version}}", brew.tag.value()))
         .content(content)
         .committer(&committer)
         .execute()
@@ -235,9 +237,49 @@ impl From<Vec<Package>> for Targets {
             })];
             target
         } else {
-            let group = value
+            // homebrew only installs on macOS and linux, and prefers glibc builds
+            // over musl ones
+            let has_gnu = |arch: &Option<Arch>| {
+                value
+                    .iter()
+                    .any(|p| p.os == Some(Os::UnknownLinuxGnu) && &p.arch == arch)
+            };
+            let candidates = value
                 .iter()
-                .cloned()
+                .filter(|p| !p.os.as_ref().is_some_and(Os::is_windows))
+                .filter(|p| p.os != Some(Os::UnknownLinuxMusl) || !has_gnu(&p.arch))
+                .filter(|p| {
+                    // the formula only has intel and arm blocks
+                    let supported = matches!(p.arch, Some(Arch::Amd64 | Arch::Arm64));
+                    if !supported {
+                        log::warn!(
+                            "Leaving {} out of the formula, it only installs x86_64 and aarch64 builds",
+                            p.name
+                        );
+                    }
+                    supported
+                })
+                .sorted_by(|a, b| (&a.os, &a.arch, &a.name).cmp(&(&b.os, &b.arch, &b.name)));
+
+            // targets such as `x86_64-unknown-linux-gnu` and its `.2.17` variant
+            // share a block, keep the first archive by name
+            let mut packages: Vec<Package> = vec![];
+            for package in candidates {
+                match packages.last() {
+                    Some(kept) if kept.os == package.os && kept.arch == package.arch => {
+                        log::warn!(
+                            "{} and {} are built for the same platform, the formula uses {}",
+                            kept.name,
+                            package.name,
+                            kept.name
+                        );
+                    }
+                    _ => packages.push(package.to_owned()),
+                }
+            }
+
+            packages
+                .into_iter()
                 .group_by(|p| p.os.to_owned())
                 .into_iter()
                 .map(|g| MultiTarget {
@@ -252,9 +294,7 @@ impl From<Vec<Package>> for Targets {
                         .collect(),
                 })
                 .map(Target::Multi)
-                .collect();
-
-            group
+                .collect()
         };
 
         Targets(v)
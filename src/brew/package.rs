@@ -27,3 +27,11 @@ impl Package {
         }
     }
 }
+
+/// What a release provides to the formula, the binary archives and the source
+/// tarball
+#[derive(Debug, Clone, Default)]
+pub struct Packages {
+    pub archives: Vec<Package>,
+    pub source: Option<Package>,
+}
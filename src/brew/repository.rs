@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Repository {
@@ -12,8 +13,8 @@ impl Repository {
     }
 }
 
-impl ToString for Repository {
-    fn to_string(&self) -> String {
-        self.url()
+impl Display for Repository {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url())
     }
 }
@@ -1,11 +1,15 @@
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum Arch {
     Amd64,
     Arm,
+    Armv7,
     Arm64,
+    I686,
 }
 
 impl Arch {
@@ -14,23 +18,37 @@ impl Arch {
     }
 }
 
-impl From<String> for Arch {
-    fn from(value: String) -> Self {
-        match value.to_lowercase().as_str() {
+impl FromStr for Arch {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let arch = match value.to_lowercase().as_str() {
             "amd64" | "x86_64" => Arch::Amd64,
             "arm" => Arch::Arm,
+            "armv7" | "thumbv7neon" => Arch::Armv7,
             "arm64" | "aarch64" => Arch::Arm64,
-            _ => panic!("Unknown arch"),
-        }
+            "i686" | "i586" | "386" | "x86" => Arch::I686,
+            _ => bail!(anyhow::anyhow!("Unknown arch: {}", value)),
+        };
+
+        Ok(arch)
+    }
+}
+
+impl From<String> for Arch {
+    fn from(value: String) -> Self {
+        value.parse().expect("Unknown arch")
     }
 }
 
-impl ToString for Arch {
-    fn to_string(&self) -> String {
+impl Display for Arch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Arch::Amd64 => "x86_64".to_string(),
-            Arch::Arm => "arm".to_string(),
-            Arch::Arm64 => "aarch64".to_string(),
+            Arch::Amd64 => write!(f, "x86_64"),
+            Arch::Arm => write!(f, "arm"),
+            Arch::Armv7 => write!(f, "armv7"),
+            Arch::Arm64 => write!(f, "aarch64"),
+            Arch::I686 => write!(f, "i686"),
         }
     }
 }
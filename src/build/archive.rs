@@ -0,0 +1,227 @@
+use super::{
+    binary::{Binaries, Binary},
+    compression::Compression,
+    os::Os,
+    post_process,
+    target::Target,
+};
+use crate::template;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_NAME_TEMPLATE: &str = "{{binary}}_{{version}}_{{arch}}_{{os}}";
+const DEFAULT_SINGLE_TARGET_NAME_TEMPLATE: &str = "{{binary}}_{{version}}";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Archive {
+    /// Glob patterns, relative to the project, of the extra files to add next
+    /// to the binary (LICENSE, README, completions, man pages...)
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Directory every file is stored under, e.g. `{{binary}}_{{version}}_{{target}}`
+    pub wrap_in_directory: Option<String>,
+    /// One archive per binary instead of a single archive holding all of them
+    #[serde(default)]
+    pub split: bool,
+    /// Name of the archives without the extension, rendered with `binary`,
+    /// `version`, `arch`, `os`, `target` and `short_commit`. Defaults to
+    /// `{{binary}}_{{version}}_{{arch}}_{{os}}`, or `{{binary}}_{{version}}`
+    /// when building for the host only
+    pub name_template: Option<String>,
+    /// Names used for `arch` and `os` in `name_template`, e.g. `x86_64 = "amd64"`
+    /// or `apple-darwin = "macos"`
+    #[serde(default)]
+    pub replacements: HashMap<String, String>,
+}
+
+/// A file stored in a release archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub executable: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct WrapData<'a> {
+    binary: &'a str,
+    version: &'a str,
+    target: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct NameData<'a> {
+    binary: &'a str,
+    version: &'a str,
+    arch: &'a str,
+    os: &'a str,
+    target: &'a str,
+    short_commit: &'a str,
+}
+
+impl Archive {
+    /// Name of the archive of `binary` built for `target`, without the extension
+    pub fn stem(
+        &self,
+        binary: &str,
+        version: &str,
+        target: &Target,
+        commit: &str,
+        multi_target: bool,
+    ) -> Result<String> {
+        let template = match &self.name_template {
+            Some(template) => template,
+            None if multi_target => DEFAULT_NAME_TEMPLATE,
+            None => DEFAULT_SINGLE_TARGET_NAME_TEMPLATE,
+        };
+        template::render(
+            template,
+            &NameData {
+                binary,
+                version,
+                arch: self.replace(target.arch_name()),
+                os: self.replace(target.os_name()),
+                target: &target.triple,
+                short_commit: commit.get(..7).unwrap_or(commit),
+            },
+        )
+    }
+
+    fn replace<'a>(&'a self, name: &'a str) -> &'a str {
+        self.replacements.get(name).map_or(name, String::as_str)
+    }
+
+    /// Binaries packaged together, each archive is named after its first binary.
+    /// Raw binaries cannot be grouped so they always get one asset each
+    pub fn group<'b>(
+        &self,
+        binaries: &'b Binaries,
+        compression: &Compression,
+    ) -> Vec<Vec<&'b Binary>> {
+        if self.split || matches!(compression, Compression::Binary) {
+            binaries.iter().map(|binary| vec![binary]).collect()
+        } else {
+            vec![binaries.iter().collect()]
+        }
+    }
+
+    /// Every file of the archive named after `binary` for `target`, starting
+    /// with the binaries
+    pub fn entries(
+        &self,
+        binary_paths: Vec<PathBuf>,
+        binary: &str,
+        version: &str,
+        target: &str,
+        base: &Path,
+    ) -> Result<Vec<ArchiveEntry>> {
+        let mut entries = binary_paths
+            .into_iter()
+            .map(|path| ArchiveEntry {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or(binary.to_string()),
+                path,
+                executable: true,
+            })
+            .collect::<Vec<ArchiveEntry>>();
+
+        for pattern in &self.files {
+            let matches = glob::glob(&base.join(pattern).to_string_lossy())?
+                .collect::<Result<Vec<PathBuf>, _>>()?;
+
+            if matches.is_empty() {
+                bail!(anyhow::anyhow!("no files match `{}`", pattern));
+            }
+
+            for path in matches {
+                for file in walk(&path)? {
+                    let name = file
+                        .strip_prefix(base)
+                        .unwrap_or(&file)
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    if entries.iter().all(|entry| entry.name != name) {
+                        entries.push(ArchiveEntry {
+                            name,
+                            path: file,
+                            executable: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(wrap_in_directory) = &self.wrap_in_directory {
+            let directory = template::render(
+                wrap_in_directory,
+                &WrapData {
+                    binary,
+                    version,
+                    target,
+                },
+            )?;
+            let directory = directory.trim_end_matches('/');
+
+            for entry in entries.iter_mut() {
+                entry.name = format!("{}/{}", directory, entry.name);
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+/// The debug info split from `binary_paths`, see [`post_process::debug_path`]
+pub fn debug_entries(binary_paths: &[PathBuf], os: &Os) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = vec![];
+    for binary_path in binary_paths {
+        let debug_path = post_process::debug_path(binary_path, os);
+        if !debug_path.exists() {
+            bail!(
+                "no debug info found at {}, please run `rr build` first",
+                debug_path.display()
+            );
+        }
+        let parent = debug_path.parent().unwrap_or(Path::new(""));
+
+        for file in walk(&debug_path)? {
+            entries.push(ArchiveEntry {
+                name: file
+                    .strip_prefix(parent)
+                    .unwrap_or(&file)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                path: file,
+                executable: false,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// `path` itself if it is a file, every file below it otherwise
+fn walk(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = vec![];
+    let mut children = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    children.sort();
+
+    for child in children {
+        files.extend(walk(&child)?);
+    }
+
+    Ok(files)
+}
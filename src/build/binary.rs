@@ -0,0 +1,93 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// A binary to build and release, either its name or a table naming the cargo
+/// package it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "BinaryConfig")]
+pub struct Binary {
+    pub name: String,
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(try_from = "BinariesConfig")]
+pub struct Binaries(pub Vec<Binary>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BinaryConfig {
+    Name(String),
+    Table {
+        name: String,
+        package: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BinariesConfig {
+    One(Binary),
+    Many(Vec<Binary>),
+}
+
+impl From<BinaryConfig> for Binary {
+    fn from(value: BinaryConfig) -> Self {
+        match value {
+            BinaryConfig::Name(name) => Binary {
+                name,
+                package: None,
+            },
+            BinaryConfig::Table { name, package } => Binary { name, package },
+        }
+    }
+}
+
+impl TryFrom<BinariesConfig> for Binaries {
+    type Error = String;
+
+    fn try_from(value: BinariesConfig) -> Result<Self, Self::Error> {
+        match value {
+            BinariesConfig::One(binary) => Ok(Binaries(vec![binary])),
+            BinariesConfig::Many(binaries) if binaries.is_empty() => {
+                Err("at least one binary is required".to_string())
+            }
+            BinariesConfig::Many(binaries) => Ok(Binaries(binaries)),
+        }
+    }
+}
+
+impl Binaries {
+    pub fn iter(&self) -> impl Iterator<Item = &Binary> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.iter().map(|binary| binary.name.to_owned()).collect()
+    }
+
+    /// Cargo arguments selecting the binaries, one cargo invocation per package
+    pub fn cargo_args(&self) -> Vec<Vec<String>> {
+        self.iter()
+            .into_group_map_by(|binary| binary.package.to_owned())
+            .into_iter()
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .map(|(package, binaries)| {
+                let mut args = vec![];
+                if let Some(package) = package {
+                    args.push("--package".to_string());
+                    args.push(package);
+                }
+                for binary in binaries {
+                    args.push("--bin".to_string());
+                    args.push(binary.name.to_owned());
+                }
+                args
+            })
+            .collect()
+    }
+}
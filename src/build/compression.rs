@@ -1,15 +1,133 @@
+use super::{arch::Arch, archive::ArchiveEntry, os::Os, target::Target};
+use anyhow::Result;
+use bzip2::write::BzEncoder;
+use flate2::write::GzEncoder;
+use glob::Pattern;
 use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+use tar::Builder;
+use xz2::write::XzEncoder;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum Compression {
     #[default]
+    #[serde(rename = "tar.gz", alias = "TarGz")]
     TarGz,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "tar.bz2")]
+    TarBz2,
+    #[serde(rename = "zip", alias = "Zip")]
+    Zip,
+    /// Upload the bare executable, without any archive
+    #[serde(rename = "binary")]
+    Binary,
+}
+
+/// Archive format used for the targets matching `os`, `arch` and the `target`
+/// triple glob pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionOverride {
+    pub os: Option<Os>,
+    pub arch: Option<Arch>,
+    pub target: Option<String>,
+    pub compression: Compression,
+}
+
+impl CompressionOverride {
+    pub fn matches(&self, target: &Target) -> bool {
+        self.os.as_ref().is_none_or(|os| os == &target.os)
+            && self.arch.as_ref().is_none_or(|arch| arch == &target.arch)
+            && self.target.as_ref().is_none_or(|pattern| {
+                Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(&target.triple))
+            })
+    }
 }
 
 impl Compression {
     pub fn extension(&self) -> &str {
         match self {
             Compression::TarGz => "tar.gz",
+            Compression::TarXz => "tar.xz",
+            Compression::TarZst => "tar.zst",
+            Compression::TarBz2 => "tar.bz2",
+            Compression::Zip => "zip",
+            Compression::Binary => "",
+        }
+    }
+
+    /// Name of the asset for `stem`, raw binaries keep the executable suffix of `os`
+    pub fn file_name(&self, stem: &str, os: &Os) -> String {
+        match self {
+            Compression::Binary => os.executable(stem),
+            _ => format!("{}.{}", stem, self.extension()),
         }
     }
+
+    /// Write `entries` to `output_path`, a raw binary only keeps the first entry
+    pub fn compress(&self, entries: &[ArchiveEntry], output_path: &Path) -> Result<()> {
+        let output = File::create(output_path)?;
+
+        match self {
+            Compression::TarGz => {
+                let encoder = GzEncoder::new(output, flate2::Compression::default());
+                tar(encoder, entries)?.finish()?;
+            }
+            Compression::TarXz => {
+                let encoder = XzEncoder::new(output, 6);
+                tar(encoder, entries)?.finish()?;
+            }
+            Compression::TarZst => {
+                let encoder = zstd::Encoder::new(output, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                tar(encoder, entries)?.finish()?;
+            }
+            Compression::TarBz2 => {
+                let encoder = BzEncoder::new(output, bzip2::Compression::default());
+                tar(encoder, entries)?.finish()?;
+            }
+            Compression::Zip => {
+                let mut archive = ZipWriter::new(output);
+
+                for entry in entries {
+                    let options = FileOptions::default()
+                        .compression_method(CompressionMethod::Deflated)
+                        .unix_permissions(if entry.executable { 0o755 } else { 0o644 });
+
+                    archive.start_file(&entry.name, options)?;
+                    io::copy(&mut File::open(&entry.path)?, &mut archive)?;
+                }
+
+                archive.finish()?;
+            }
+            Compression::Binary => {
+                if entries.len() > 1 {
+                    log::warn!("raw binaries cannot hold extra files, ignoring them");
+                }
+                drop(output);
+                fs::copy(&entries[0].path, output_path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn tar<W>(writer: W, entries: &[ArchiveEntry]) -> Result<W>
+where
+    W: Write,
+{
+    let mut archive = Builder::new(writer);
+
+    for entry in entries {
+        archive.append_path_with_name(&entry.path, &entry.name)?;
+    }
+
+    Ok(archive.into_inner()?)
 }
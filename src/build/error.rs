@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("could not run `{command}` for {target}: {source}")]
+    Spawn {
+        target: String,
+        command: String,
+        source: std::io::Error,
+    },
+    #[error("`{command}` failed for {target} with {}\n{stderr}", exit_code(.code))]
+    Failed {
+        target: String,
+        command: String,
+        code: Option<i32>,
+        /// Last lines the command wrote to stderr
+        stderr: String,
+    },
+}
+
+fn exit_code(code: &Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exit code {}", code),
+        None => "no exit code (killed by a signal)".to_string(),
+    }
+}
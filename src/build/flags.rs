@@ -0,0 +1,90 @@
+use super::target::Target;
+use anyhow::{Context, Result};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+/// Cargo options of a build
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Flags {
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub no_default_features: Option<bool>,
+    /// Cargo profile, `release` when not set
+    pub profile: Option<String>,
+    /// Extra arguments given to the build command
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// `KEY=VALUE` environment of the build command, e.g. `RUSTFLAGS=-C strip=symbols`
+    #[serde(default)]
+    pub env: Vec<String>,
+    pub locked: Option<bool>,
+}
+
+/// Flags used for the targets matching the `target` glob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetOverride {
+    pub target: String,
+    #[serde(flatten)]
+    pub flags: Flags,
+}
+
+impl TargetOverride {
+    pub fn matches(&self, target: &Target) -> bool {
+        Pattern::new(&self.target).is_ok_and(|pattern| pattern.matches(&target.triple))
+    }
+}
+
+impl Flags {
+    /// `other` on top of these flags, lists and env are extended and the
+    /// options it sets replace ours
+    pub fn merge(&self, other: &Flags) -> Flags {
+        let mut flags = self.to_owned();
+        flags.features.extend(other.features.iter().cloned());
+        flags.flags.extend(other.flags.iter().cloned());
+        flags.env.extend(other.env.iter().cloned());
+        flags.no_default_features = other.no_default_features.or(self.no_default_features);
+        flags.profile = other.profile.to_owned().or(flags.profile);
+        flags.locked = other.locked.or(self.locked);
+        flags
+    }
+
+    /// Directory cargo puts the binaries of the profile in
+    pub fn profile_dir(&self) -> &str {
+        match self.profile.as_deref() {
+            None | Some("release") | Some("bench") => "release",
+            Some("dev") | Some("test") => "debug",
+            Some(profile) => profile,
+        }
+    }
+
+    /// Variables set on the build command
+    pub fn envs(&self) -> Result<Vec<(&str, &str)>> {
+        self.env
+            .iter()
+            .map(|var| {
+                var.split_once('=')
+                    .with_context(|| format!("invalid env `{}`, expected KEY=VALUE", var))
+            })
+            .collect()
+    }
+
+    /// Arguments of `cargo build`, without the target and packages
+    pub fn args(&self) -> Vec<String> {
+        let mut args = match self.profile.as_deref() {
+            None | Some("release") => vec!["--release".to_string()],
+            Some(profile) => vec!["--profile".to_string(), profile.to_string()],
+        };
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.no_default_features.unwrap_or(false) {
+            args.push("--no-default-features".to_string());
+        }
+        if self.locked.unwrap_or(false) {
+            args.push("--locked".to_string());
+        }
+        args.extend(self.flags.iter().cloned());
+        args
+    }
+}
@@ -0,0 +1,78 @@
+use super::process;
+use crate::template;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Shell commands run around the build, rendered with `version` (empty when
+/// HEAD is not tagged), `target`, `binary` (the path of the built binary) and
+/// `name`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Hooks {
+    /// Run once before any target is built
+    #[serde(default)]
+    pub before: Vec<String>,
+    /// Run once after every target is built
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Run before the build of each target
+    #[serde(default)]
+    pub pre: Vec<String>,
+    /// Run for each binary once its target is built
+    #[serde(default)]
+    pub post: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct HookData<'a> {
+    pub version: &'a str,
+    pub target: &'a str,
+    pub binary: Option<String>,
+    pub name: Option<&'a str>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.before.is_empty()
+            && self.after.is_empty()
+            && self.pre.is_empty()
+            && self.post.is_empty()
+    }
+}
+
+/// Run `hooks` one after the other in `path`, stopping at the first failure
+pub async fn run(
+    hooks: &[String],
+    data: &HookData<'_>,
+    path: &Path,
+    name: &str,
+    dry_run: bool,
+) -> Result<()> {
+    for hook in hooks {
+        let hook = template::render(hook, data)?;
+        let mut cmd = shell(&hook);
+        cmd.current_dir(path);
+
+        if dry_run {
+            log::info!("Would run hook: {}", hook);
+        } else {
+            log::info!("Running hook: {}", hook);
+            process::run(name, cmd).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
@@ -1,39 +1,192 @@
 pub mod arch;
+pub mod archive;
+pub mod binary;
 pub mod committer;
 pub mod compression;
+pub mod error;
+pub mod flags;
+pub mod hooks;
 pub mod os;
+pub mod post_process;
+pub mod process;
+pub mod summary;
+pub mod target;
+pub mod tool;
 
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
-use self::compression::Compression;
-use anyhow::Result;
+use self::{
+    archive::Archive,
+    binary::Binaries,
+    compression::{Compression, CompressionOverride},
+    flags::{Flags, TargetOverride},
+    hooks::{HookData, Hooks},
+    post_process::{BinarySize, PostProcess},
+};
+use crate::github::tag::Tag;
+use anyhow::{bail, Context, Result};
 use arch::Arch;
+use glob::Pattern;
 use os::Os;
 use serde::{Deserialize, Serialize};
+use summary::{TargetReport, TargetStatus};
+use target::Target;
+use tokio::{sync::Semaphore, task::JoinSet};
+use tool::Tool;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Build {
     pub arch: Option<Vec<Arch>>,
     pub os: Option<Vec<Os>>,
-    pub binary: String,
+    /// Explicit target triples, used instead of the `arch` and `os` product
+    pub targets: Option<Vec<String>>,
+    /// Glob patterns of the target triples not to build
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// The binaries of the package, or of the whole workspace, by default
+    #[serde(default, alias = "binary")]
+    pub binaries: Binaries,
+    pub compression: Option<Compression>,
     #[serde(default)]
-    pub compression: Compression,
+    pub compression_overrides: Vec<CompressionOverride>,
     #[serde(default)]
     pub tool: Tool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub enum Tool {
-    #[serde(rename = "cargo")]
-    #[default]
-    Cargo,
-    #[serde(rename = "cross")]
-    Cross,
+    #[serde(flatten)]
+    pub flags: Flags,
+    /// Flags of the matching targets, applied in order on top of `flags`
+    #[serde(default)]
+    pub target_overrides: Vec<TargetOverride>,
+    #[serde(default)]
+    pub archive: Archive,
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(default)]
+    pub post_process: PostProcess,
+    /// Number of targets built at the same time, defaults to the number of cpus
+    pub parallelism: Option<usize>,
 }
 
 impl Build {
+    /// Archive format for binaries built for `target`, the first matching
+    /// override wins and Windows defaults to zip
+    pub fn compression(&self, target: &Target) -> Compression {
+        let compression = self
+            .compression_overrides
+            .iter()
+            .find(|o| o.matches(target))
+            .map(|o| &o.compression)
+            .or(self.compression.as_ref());
+
+        match compression {
+            Some(compression) => compression.to_owned(),
+            None if target.os.is_windows() => Compression::Zip,
+            None => Compression::default(),
+        }
+    }
+
+    /// Every target to build, either the explicit triples or the `arch` and
+    /// `os` product, without the ignored ones
+    pub fn targets(&self) -> Result<Vec<Target>> {
+        let targets = match &self.targets {
+            Some(triples) => triples
+                .iter()
+                .map(|triple| Target::parse(triple))
+                .collect::<Result<Vec<Target>>>()?,
+            None => {
+                let mut targets = vec![];
+                for arch in self.arch.as_ref().unwrap_or(&vec![]) {
+                    for os in self.os.as_ref().unwrap_or(&vec![]) {
+                        targets.push(Target::new(arch, os));
+                    }
+                }
+                targets
+            }
+        };
+
+        let ignore = self
+            .ignore
+            .iter()
+            .map(|pattern| Pattern::new(pattern))
+            .collect::<Result<Vec<Pattern>, _>>()?;
+
+        Ok(targets
+            .into_iter()
+            .filter(|target| !ignore.iter().any(|p| p.matches(&target.triple)))
+            .collect())
+    }
+
+    /// Targets the binaries are packaged for, the host without any target
+    pub fn archive_targets(&self) -> Result<Vec<Target>> {
+        if self.is_multi_target() {
+            self.targets()
+        } else {
+            Ok(vec![Target::current()])
+        }
+    }
+
+    /// Whether the binaries of some target are packaged in several archives
+    pub fn splits_binaries(&self) -> Result<bool> {
+        if self.binaries.iter().count() < 2 {
+            return Ok(false);
+        }
+        Ok(self.archive_targets()?.iter().any(|target| {
+            self.archive
+                .group(&self.binaries, &self.compression(target))
+                .len()
+                > 1
+        }))
+    }
+
+    /// Cargo options of the build for `target`, or for the host without a target
+    pub fn flags(&self, target: Option<&Target>) -> Flags {
+        self.target_overrides
+            .iter()
+            .filter(|o| target.is_some_and(|target| o.matches(target)))
+            .fold(self.flags.to_owned(), |flags, o| flags.merge(&o.flags))
+    }
+
+    /// `CARGO_TARGET_DIR` of the build for `target`. Every target gets its own
+    /// so concurrent builds never wait on the lock of a shared directory, and
+    /// cross containers never share build artifacts. Custom tools pick their
+    /// own directory, the default one is expected
+    pub fn target_dir(&self, base: &Path, target: Option<&Target>) -> PathBuf {
+        match (&self.tool, target) {
+            (Tool::Cross, Some(target)) => base.join("target").join("cross").join(&target.triple),
+            (Tool::Custom { .. }, _) | (_, None) => base.join("target"),
+            (_, Some(target)) => base.join("target").join("rr").join(&target.triple),
+        }
+    }
+
+    /// Path of `binary` built for `target`, or for the host without a target
+    pub fn binary_path(&self, base: &Path, target: Option<&Target>, binary: &str) -> PathBuf {
+        let flags = self.flags(target);
+        match target {
+            Some(target) => self
+                .target_dir(base, Some(target))
+                .join(&target.triple)
+                .join(flags.profile_dir())
+                .join(target.os.executable(binary)),
+            None => self
+                .target_dir(base, None)
+                .join(flags.profile_dir())
+                .join(Os::current().executable(binary)),
+        }
+    }
+
     pub fn is_multi_target(&self) -> bool {
-        self.is_multi_arch() || self.is_multi_os()
+        self.is_multi_arch() || self.is_multi_os() || self.has_targets()
+    }
+
+    pub fn has_targets(&self) -> bool {
+        if let Some(targets) = &self.targets {
+            !targets.is_empty()
+        } else {
+            false
+        }
     }
 
     pub fn is_multi_arch(&self) -> bool {
@@ -53,77 +206,200 @@ impl Build {
     }
 }
 
-pub async fn build(build_info: &Build, path: PathBuf, dry_run: bool) -> Result<()> {
-    if build_info.is_multi_target() {
+pub async fn build(
+    build_info: &Build,
+    path: PathBuf,
+    tag: Option<&Tag>,
+    dry_run: bool,
+    parallelism: Option<usize>,
+    keep_going: bool,
+) -> Result<Vec<String>> {
+    if build_info.binaries.is_empty() {
+        bail!("no binary to build, set build.binary");
+    }
+    // the tools get `CARGO_TARGET_DIR` while running in `path`, so a relative
+    // path would be applied twice
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("cannot find {}", path.display()))?;
+
+    let targets = if build_info.is_multi_target() {
         log::info!("Building for multiple targets");
-        for arch in build_info.arch.as_ref().unwrap_or(&vec![]) {
-            for os in build_info.os.as_ref().unwrap_or(&vec![]) {
-                log::debug!("Building for arch: {:?} and os: {:?}", arch, os);
-                build_target(&build_info, &path, &arch, &os, dry_run).await?;
-            }
-        }
+        build_info.targets()?.into_iter().map(Some).collect()
     } else {
         log::info!("Building for single target");
-        build_target(
-            &build_info,
-            &path,
-            &Arch::current(),
-            &Os::current(),
-            dry_run,
-        )
-        .await?;
+        vec![None]
+    };
+
+    let parallelism = parallelism
+        .or(build_info.parallelism)
+        .or(std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+    log::debug!("Building {} targets at a time", parallelism);
+
+    let version = tag.map(|tag| tag.value().to_owned()).unwrap_or_default();
+    let data = HookData {
+        version: &version,
+        ..Default::default()
+    };
+    hooks::run(
+        &build_info.hooks.before,
+        &data,
+        &path,
+        "before hooks",
+        dry_run,
+    )
+    .await?;
+
+    let build_info = Arc::new(build_info.to_owned());
+    let semaphore = Arc::new(Semaphore::new(parallelism));
+    let mut tasks = JoinSet::new();
+
+    let names: Vec<String> = targets
+        .iter()
+        .map(|target| {
+            target
+                .as_ref()
+                .map(|target| target.triple.to_owned())
+                .unwrap_or_else(|| "host".to_string())
+        })
+        .collect();
+
+    for (index, target) in targets.into_iter().enumerate() {
+        let build_info = build_info.clone();
+        let semaphore = semaphore.clone();
+        let path = path.clone();
+        let name = names[index].to_owned();
+        let version = version.to_owned();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let start = Instant::now();
+
+            log::info!("Building for target: {}", name);
+            let result = build_target(&build_info, &path, target.as_ref(), &version, dry_run).await;
+
+            let (status, sizes) = match result {
+                Ok(sizes) => (TargetStatus::Success, sizes),
+                Err(err) => (TargetStatus::Failed(format!("{:#}", err)), vec![]),
+            };
+            let report = TargetReport {
+                target: name,
+                duration: start.elapsed(),
+                status,
+                sizes,
+            };
+            anyhow::Ok((index, report))
+        });
     }
 
-    Ok(())
+    let mut reports: Vec<Option<TargetReport>> = names.iter().map(|_| None).collect();
+    while let Some(result) = tasks.join_next().await {
+        let (index, report) = match result {
+            Ok(result) => result?,
+            Err(err) if err.is_cancelled() => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if !report.is_success() && !keep_going {
+            log::error!(
+                "Build for {} failed, stopping the other builds",
+                report.target
+            );
+            tasks.abort_all();
+        }
+        reports[index] = Some(report);
+    }
+    let reports: Vec<TargetReport> = reports
+        .into_iter()
+        .zip(names)
+        .map(|(report, target)| {
+            report.unwrap_or(TargetReport {
+                target,
+                duration: Default::default(),
+                status: TargetStatus::Cancelled,
+                sizes: vec![],
+            })
+        })
+        .collect();
+
+    summary::log_summary(&reports);
+    summary::log_sizes(&reports);
+
+    let mut failed = vec![];
+    for report in &reports {
+        if let TargetStatus::Failed(error) = &report.status {
+            log::error!("Build for {} failed: {}", report.target, error);
+            failed.push(report.target.to_owned());
+        }
+    }
+    if !failed.is_empty() && (!keep_going || failed.len() == reports.len()) {
+        bail!("{} of {} builds failed", failed.len(), reports.len());
+    }
+
+    hooks::run(
+        &build_info.hooks.after,
+        &data,
+        &path,
+        "after hooks",
+        dry_run,
+    )
+    .await?;
+
+    Ok(failed)
 }
 
 async fn build_target(
     build_info: &Build,
-    path: &PathBuf,
-    arch: &Arch,
-    os: &Os,
+    path: &Path,
+    target: Option<&Target>,
+    version: &str,
     dry_run: bool,
-) -> Result<()> {
-    let toolchain = os_arch_to_toolchain(os, arch);
-    let mut cmd = match &build_info.tool {
-        Tool::Cargo => {
-            let mut cmd = tokio::process::Command::new("cargo");
-            cmd.arg("build");
-            cmd.arg("--release");
-            cmd.arg("--target").arg(toolchain);
-            cmd.current_dir(path);
-            cmd
-        }
-        Tool::Cross => {
-            let mut cmd = tokio::process::Command::new("cross");
-            cmd.arg("build");
-            cmd.arg("--release");
-            cmd.arg("--target").arg(toolchain);
-            cmd.current_dir(path);
-            cmd
-        }
+) -> Result<Vec<BinarySize>> {
+    let name = target.map_or("host", |target| target.triple.as_str());
+    let data = HookData {
+        version,
+        target: target.map_or("", |target| target.triple.as_str()),
+        ..Default::default()
     };
+    hooks::run(&build_info.hooks.pre, &data, path, name, dry_run).await?;
 
-    if dry_run {
-        log::info!("Would run: {:?}", cmd);
-    } else {
-        cmd.status().await?;
+    let flags = build_info.flags(target);
+    let envs = flags.envs()?;
+    for args in build_info.binaries.cargo_args() {
+        let mut cmd = build_info.tool.command(
+            path,
+            target,
+            &build_info.target_dir(path, target),
+            flags.profile.as_deref().unwrap_or("release"),
+        )?;
+        cmd.args(flags.args());
+        cmd.args(args);
+        cmd.envs(envs.iter().copied());
+
+        if dry_run {
+            log::info!("Would run: {:?}", cmd);
+        } else {
+            process::run(name, cmd).await?;
+        }
     }
 
-    Ok(())
-}
+    let mut sizes = vec![];
+    for binary in build_info.binaries.iter() {
+        let binary_path = build_info.binary_path(path, target, &binary.name);
+        let size = build_info
+            .post_process
+            .run(&binary.name, &binary_path, target, name, dry_run)
+            .await?;
+        sizes.extend(size);
 
-fn os_arch_to_toolchain(os: &Os, arch: &Arch) -> String {
-    format!(
-        "{}-{}",
-        match arch {
-            Arch::Amd64 => "x86_64",
-            Arch::Arm => "arm",
-            Arch::Arm64 => "aarch64",
-        },
-        match os {
-            Os::UnknownLinuxGnu => "unknown-linux-gnu",
-            Os::AppleDarwin => "apple-darwin",
-        },
-    )
+        let data = HookData {
+            binary: Some(binary_path.display().to_string()),
+            name: Some(&binary.name),
+            ..data
+        };
+        hooks::run(&build_info.hooks.post, &data, path, name, dry_run).await?;
+    }
+
+    Ok(sizes)
 }
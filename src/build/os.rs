@@ -1,34 +1,74 @@
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Os {
     #[serde(rename(deserialize = "darwin"))]
     AppleDarwin,
     #[serde(rename(deserialize = "linux"))]
     UnknownLinuxGnu,
+    #[serde(rename(deserialize = "linux-musl"))]
+    UnknownLinuxMusl,
+    #[serde(rename(deserialize = "windows"), alias = "windows-msvc")]
+    PcWindowsMsvc,
+    #[serde(rename(deserialize = "windows-gnu"))]
+    PcWindowsGnu,
 }
 
 impl Os {
     pub fn current() -> Self {
         std::env::consts::OS.to_string().into()
     }
+
+    pub fn is_windows(&self) -> bool {
+        matches!(self, Os::PcWindowsMsvc | Os::PcWindowsGnu)
+    }
+
+    /// File name of the executable built for this os, e.g. `foo.exe` on Windows
+    pub fn executable(&self, name: &str) -> String {
+        if self.is_windows() {
+            format!("{}.exe", name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+impl FromStr for Os {
+    type Err = anyhow::Error;
+
+    /// Parse an os name, or the vendor-os-env part of a target triple
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let os = match value.to_lowercase().as_str() {
+            "apple-darwin" | "darwin" | "macos" => Os::AppleDarwin,
+            "linux" => Os::UnknownLinuxGnu,
+            "linux-musl" => Os::UnknownLinuxMusl,
+            "pc-windows-msvc" | "windows-msvc" | "windows" => Os::PcWindowsMsvc,
+            "pc-windows-gnu" | "windows-gnu" => Os::PcWindowsGnu,
+            os if os.starts_with("unknown-linux-gnu") => Os::UnknownLinuxGnu,
+            os if os.starts_with("unknown-linux-musl") => Os::UnknownLinuxMusl,
+            _ => bail!(anyhow::anyhow!("Unknown os: {}", value)),
+        };
+
+        Ok(os)
+    }
 }
 
 impl From<String> for Os {
     fn from(value: String) -> Self {
-        match value.to_lowercase().as_str() {
-            "apple-darwin" | "darwin" | "macos" => Os::AppleDarwin,
-            "unknown-linux-gnu" | "linux" => Os::UnknownLinuxGnu,
-            _ => panic!("Unknown arch"),
-        }
+        value.parse().expect("Unknown os")
     }
 }
 
-impl ToString for Os {
-    fn to_string(&self) -> String {
+impl Display for Os {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Os::AppleDarwin => "apple-darwin".to_string(),
-            Os::UnknownLinuxGnu => "unknown-linux-gnu".to_string(),
+            Os::AppleDarwin => write!(f, "apple-darwin"),
+            Os::UnknownLinuxGnu => write!(f, "unknown-linux-gnu"),
+            Os::UnknownLinuxMusl => write!(f, "unknown-linux-musl"),
+            Os::PcWindowsMsvc => write!(f, "pc-windows-msvc"),
+            Os::PcWindowsGnu => write!(f, "pc-windows-gnu"),
         }
     }
 }
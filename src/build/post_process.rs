@@ -0,0 +1,140 @@
+use super::{os::Os, process, target::Target};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Processing applied to the binaries once built
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PostProcess {
+    /// Remove the debug symbols of the binaries
+    #[serde(default)]
+    pub strip: bool,
+    /// Strip command, `strip` for the host and `llvm-strip` for other targets by default
+    pub strip_command: Option<String>,
+    /// Compress the binaries with UPX
+    #[serde(default)]
+    pub upx: bool,
+    #[serde(default)]
+    pub upx_args: Vec<String>,
+    /// Keep the debug info next to each binary, to be released as a separate asset
+    #[serde(default)]
+    pub split_debug: bool,
+    /// objcopy command used to split the debug info of ELF and mingw binaries,
+    /// `objcopy` for the host and `llvm-objcopy` for other targets by default
+    pub objcopy_command: Option<String>,
+}
+
+/// Size of a binary before and after post-processing
+#[derive(Debug, Clone)]
+pub struct BinarySize {
+    pub binary: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl PostProcess {
+    fn strip_command(&self, target: Option<&Target>) -> &str {
+        match (&self.strip_command, target) {
+            (Some(command), _) => command,
+            (None, Some(target)) if target != &Target::current() => "llvm-strip",
+            (None, _) => "strip",
+        }
+    }
+
+    fn objcopy_command(&self, target: Option<&Target>) -> &str {
+        match (&self.objcopy_command, target) {
+            (Some(command), _) => command,
+            (None, Some(target)) if target != &Target::current() => "llvm-objcopy",
+            (None, _) => "objcopy",
+        }
+    }
+
+    /// Process `binary_path` and report its size, nothing is measured in dry run
+    pub async fn run(
+        &self,
+        binary: &str,
+        binary_path: &Path,
+        target: Option<&Target>,
+        name: &str,
+        dry_run: bool,
+    ) -> Result<Option<BinarySize>> {
+        let before = if dry_run {
+            0
+        } else {
+            std::fs::metadata(binary_path)?.len()
+        };
+
+        let os = target.map_or_else(Os::current, |target| target.os.to_owned());
+        let debug_path = debug_path(binary_path, &os);
+        let objcopy = self.split_debug && !matches!(os, Os::AppleDarwin | Os::PcWindowsMsvc);
+        if self.split_debug && os == Os::AppleDarwin {
+            let dsymutil = match target {
+                Some(target) if target != &Target::current() => "llvm-dsymutil",
+                _ => "dsymutil",
+            };
+            let mut cmd = Command::new(dsymutil);
+            cmd.arg(binary_path).arg("-o").arg(&debug_path);
+            run(cmd, name, dry_run).await?;
+        }
+        if objcopy {
+            let mut cmd = Command::new(self.objcopy_command(target));
+            cmd.arg("--only-keep-debug")
+                .arg(binary_path)
+                .arg(&debug_path);
+            run(cmd, name, dry_run).await?;
+        }
+
+        // msvc keeps the debug info in a separate pdb file
+        if self.strip && os != Os::PcWindowsMsvc {
+            let mut cmd = Command::new(self.strip_command(target));
+            cmd.arg(binary_path);
+            run(cmd, name, dry_run).await?;
+        }
+        if objcopy {
+            let mut cmd = Command::new(self.objcopy_command(target));
+            cmd.arg(format!("--add-gnu-debuglink={}", debug_path.display()))
+                .arg(binary_path);
+            run(cmd, name, dry_run).await?;
+        }
+        if self.upx {
+            let mut cmd = Command::new("upx");
+            cmd.args(&self.upx_args).arg(binary_path);
+            run(cmd, name, dry_run).await?;
+        }
+
+        if dry_run {
+            return Ok(None);
+        }
+        Ok(Some(BinarySize {
+            binary: binary.to_string(),
+            before,
+            after: std::fs::metadata(binary_path)?.len(),
+        }))
+    }
+}
+
+/// Where the debug info of `binary_path` is split to: a dSYM bundle on macOS,
+/// the pdb cargo writes for msvc and a `.debug` file everywhere else
+pub fn debug_path(binary_path: &Path, os: &Os) -> PathBuf {
+    match os {
+        Os::AppleDarwin => PathBuf::from(format!("{}.dSYM", binary_path.display())),
+        Os::PcWindowsMsvc => {
+            let stem = binary_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().replace('-', "_"))
+                .unwrap_or_default();
+            binary_path.with_file_name(format!("{}.pdb", stem))
+        }
+        _ => PathBuf::from(format!("{}.debug", binary_path.display())),
+    }
+}
+
+async fn run(cmd: Command, name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        log::info!("Would run: {:?}", cmd);
+    } else {
+        process::run(name, cmd).await?;
+    }
+    Ok(())
+}
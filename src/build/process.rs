@@ -0,0 +1,53 @@
+use super::error::BuildError;
+use std::{collections::VecDeque, process::Stdio};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+};
+
+/// Number of stderr lines kept to explain a failure
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Run `cmd` for `target`, forwarding its stderr while keeping its last lines
+pub async fn run(target: &str, mut cmd: Command) -> Result<(), BuildError> {
+    let command = format!("{:?}", cmd.as_std());
+
+    let mut child = cmd
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|source| BuildError::Spawn {
+            target: target.to_string(),
+            command: command.to_owned(),
+            source,
+        })?;
+
+    let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    if let Some(stderr) = child.stderr.take() {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            eprintln!("{}", line);
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+
+    let status = child.wait().await.map_err(|source| BuildError::Spawn {
+        target: target.to_string(),
+        command: command.to_owned(),
+        source,
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(BuildError::Failed {
+            target: target.to_string(),
+            command,
+            code: status.code(),
+            stderr: Vec::from(tail).join("\n"),
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use super::post_process::BinarySize;
+use std::time::Duration;
+
+/// Outcome of the build of a single target
+#[derive(Debug)]
+pub struct TargetReport {
+    pub target: String,
+    pub duration: Duration,
+    pub status: TargetStatus,
+    pub sizes: Vec<BinarySize>,
+}
+
+#[derive(Debug)]
+pub enum TargetStatus {
+    Success,
+    Failed(String),
+    /// Stopped because another target failed
+    Cancelled,
+}
+
+impl TargetReport {
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, TargetStatus::Success)
+    }
+}
+
+pub fn log_summary(reports: &[TargetReport]) {
+    let width = reports
+        .iter()
+        .map(|report| report.target.len())
+        .max()
+        .unwrap_or_default();
+
+    log::info!("Build summary:");
+    for report in reports {
+        log::info!(
+            "  {:width$}  {:7}  {:>8.1}s",
+            report.target,
+            match report.status {
+                TargetStatus::Success => "ok",
+                TargetStatus::Failed(_) => "failed",
+                TargetStatus::Cancelled => "skipped",
+            },
+            report.duration.as_secs_f64(),
+            width = width
+        );
+    }
+}
+
+/// Size of every binary before and after post-processing
+pub fn log_sizes(reports: &[TargetReport]) {
+    let sizes: Vec<(&str, &BinarySize)> = reports
+        .iter()
+        .flat_map(|report| {
+            report
+                .sizes
+                .iter()
+                .map(|size| (report.target.as_str(), size))
+        })
+        .collect();
+    if sizes.is_empty() {
+        return;
+    }
+
+    let target_width = sizes.iter().map(|(t, _)| t.len()).max().unwrap_or_default();
+    let binary_width = sizes
+        .iter()
+        .map(|(_, size)| size.binary.len())
+        .max()
+        .unwrap_or_default();
+
+    log::info!("Binary sizes:");
+    for (target, size) in sizes {
+        let change = if size.before == 0 {
+            0.0
+        } else {
+            (size.after as f64 - size.before as f64) / size.before as f64 * 100.0
+        };
+        log::info!(
+            "  {:target_width$}  {:binary_width$}  {:>10} -> {:>10}  {:>+6.1}%",
+            target,
+            size.binary,
+            format_size(size.before),
+            format_size(size.after),
+            change,
+            target_width = target_width,
+            binary_width = binary_width
+        );
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
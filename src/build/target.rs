@@ -0,0 +1,89 @@
+use super::{arch::Arch, os::Os};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// A rust target triple with the arch and os it builds for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Target {
+    pub triple: String,
+    pub arch: Arch,
+    pub os: Os,
+    /// glibc version appended to the triple for zigbuild, e.g. `2.17`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glibc: Option<String>,
+}
+
+impl Target {
+    pub fn new(arch: &Arch, os: &Os) -> Self {
+        Target {
+            triple: format!("{}-{}", arch, os),
+            arch: arch.to_owned(),
+            os: os.to_owned(),
+            glibc: None,
+        }
+    }
+
+    /// The target of the machine running the release
+    pub fn current() -> Self {
+        Target::new(&Arch::current(), &Os::current())
+    }
+
+    /// Parse `triple`, optionally followed by a glibc version like
+    /// `x86_64-unknown-linux-gnu.2.17`
+    pub fn parse(triple: &str) -> Result<Self> {
+        let (triple, glibc) = match triple.split_once('.') {
+            Some((triple, glibc)) => {
+                if glibc.is_empty() || !glibc.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                    bail!(
+                        "invalid glibc version in target triple: {}.{}",
+                        triple,
+                        glibc
+                    );
+                }
+                (triple, Some(glibc.to_string()))
+            }
+            None => (triple, None),
+        };
+        let (arch, os) = triple
+            .split_once('-')
+            .with_context(|| format!("invalid target triple: {}", triple))?;
+
+        Ok(Target {
+            triple: triple.to_string(),
+            arch: arch
+                .parse()
+                .with_context(|| format!("invalid target triple: {}", triple))?,
+            os: os
+                .parse()
+                .with_context(|| format!("invalid target triple: {}", triple))?,
+            glibc,
+        })
+    }
+
+    /// The triple given to the build tool, with the glibc version if any
+    pub fn build_triple(&self) -> String {
+        match &self.glibc {
+            Some(glibc) => format!("{}.{}", self.triple, glibc),
+            None => self.triple.to_owned(),
+        }
+    }
+
+    /// Architecture part of the triple, e.g. `armv7`
+    pub fn arch_name(&self) -> &str {
+        self.triple
+            .split_once('-')
+            .map_or(&self.triple, |(arch, _)| arch)
+    }
+
+    /// Everything after the architecture, e.g. `unknown-linux-gnueabihf`
+    pub fn os_name(&self) -> &str {
+        self.triple.split_once('-').map_or("", |(_, os)| os)
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.triple)
+    }
+}
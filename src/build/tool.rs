@@ -0,0 +1,102 @@
+use super::target::Target;
+use crate::template;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub enum Tool {
+    #[serde(rename = "cargo")]
+    #[default]
+    Cargo,
+    #[serde(rename = "cross")]
+    Cross,
+    /// `cargo zigbuild`, the only tool accepting a glibc version in the target
+    #[serde(rename = "zigbuild")]
+    Zigbuild,
+    /// `cargo xwin build`, for msvc targets
+    #[serde(rename = "xwin")]
+    Xwin,
+    /// Any command taking the same arguments as `cargo build` after `args`
+    #[serde(rename = "custom")]
+    Custom {
+        command: String,
+        /// Templates rendered with `target`, `arch`, `os` and `profile`
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Serialize)]
+struct ArgsData<'a> {
+    target: &'a str,
+    arch: &'a str,
+    os: &'a str,
+    profile: &'a str,
+}
+
+impl Tool {
+    /// Build command for `target`, up to the cargo arguments
+    pub fn command(
+        &self,
+        path: &Path,
+        target: Option<&Target>,
+        target_dir: &Path,
+        profile: &str,
+    ) -> Result<Command> {
+        if let Some(target) = target.filter(|target| target.glibc.is_some()) {
+            if !matches!(self, Tool::Zigbuild) {
+                bail!(
+                    "target {} has a glibc version, it can only be built with zigbuild",
+                    target.build_triple()
+                );
+            }
+        }
+
+        let mut cmd = match self {
+            Tool::Cargo => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("build");
+                cmd
+            }
+            Tool::Cross => {
+                let mut cmd = Command::new("cross");
+                cmd.arg("build");
+                cmd
+            }
+            Tool::Zigbuild => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("zigbuild");
+                cmd
+            }
+            Tool::Xwin => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("xwin").arg("build");
+                cmd
+            }
+            Tool::Custom { command, args } => {
+                let data = ArgsData {
+                    target: target.map_or("", |target| target.triple.as_str()),
+                    arch: target.map_or("", |target| target.arch_name()),
+                    os: target.map_or("", |target| target.os_name()),
+                    profile,
+                };
+                let mut cmd = Command::new(command);
+                for arg in args {
+                    cmd.arg(template::render(arg, &data)?);
+                }
+                cmd
+            }
+        };
+        cmd.current_dir(path);
+        if let Some(target) = target {
+            cmd.arg("--target").arg(target.build_triple());
+            if !matches!(self, Tool::Custom { .. }) {
+                cmd.env("CARGO_TARGET_DIR", target_dir);
+            }
+        }
+
+        Ok(cmd)
+    }
+}
@@ -0,0 +1,273 @@
+use crate::git::{self, changelog};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use semver::Version;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Document, Item, Value};
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    /// Major for breaking changes, minor for features and patch otherwise
+    Auto,
+}
+
+impl Bump {
+    /// The bump required by the Conventional Commits since the latest tag,
+    /// breaking changes only bump the minor version before 1.0.0
+    fn from_commits(base: &Path, prefix: Option<&str>, current: &Version) -> Result<Bump> {
+        let commits = changelog::unreleased(base, prefix)?;
+        if commits.is_empty() {
+            bail!("no commits since {}, nothing to release", current);
+        }
+
+        if commits.iter().any(|commit| commit.breaking) {
+            Ok(if current.major == 0 {
+                Bump::Minor
+            } else {
+                Bump::Major
+            })
+        } else if commits
+            .iter()
+            .any(|commit| commit.kind.as_deref() == Some("feat"))
+        {
+            Ok(Bump::Minor)
+        } else {
+            Ok(Bump::Patch)
+        }
+    }
+
+    pub fn apply(&self, current: &Version) -> Version {
+        match self {
+            Bump::Major => Version::new(current.major + 1, 0, 0),
+            Bump::Minor => Version::new(current.major, current.minor + 1, 0),
+            Bump::Patch | Bump::Auto => {
+                if current.pre.is_empty() {
+                    Version::new(current.major, current.minor, current.patch + 1)
+                } else {
+                    Version::new(current.major, current.minor, current.patch)
+                }
+            }
+        }
+    }
+}
+
+/// Bump the version of every package of the workspace at `base`, then commit
+/// the manifests and tag the commit with `prefix` and the version. The working
+/// tree must be clean so that the commit holds nothing but the bump
+pub fn bump(
+    base: &Path,
+    bump: &Bump,
+    prefix: Option<&str>,
+    push: Option<&str>,
+    dry_run: bool,
+) -> Result<Version> {
+    let dirty = git::dirty_files(base)?;
+    if !dirty.is_empty() {
+        bail!(
+            "the working tree has uncommitted changes: {}, commit or stash them before bumping",
+            dirty.join(", ")
+        );
+    }
+
+    let manifests = manifests(base)?;
+    let current = match git::latest_version(base, prefix)? {
+        Some(version) => version,
+        None => package_version(&manifests[0])?,
+    };
+
+    let bump = match bump {
+        Bump::Auto => Bump::from_commits(base, prefix, &current)?,
+        bump => bump.to_owned(),
+    };
+    let version = bump.apply(&current);
+    log::info!("Bumping {:?} version: {} -> {}", bump, current, version);
+
+    let mut documents = vec![];
+    for manifest in &manifests {
+        let document: Document = fs::read_to_string(manifest)?
+            .parse()
+            .with_context(|| format!("invalid manifest {}", manifest.display()))?;
+        documents.push(document);
+    }
+    let packages: Vec<String> = documents
+        .iter()
+        .filter_map(|document| document.get("package")?.get("name")?.as_str())
+        .map(str::to_string)
+        .collect();
+
+    let mut changed = vec![];
+    for (manifest, mut document) in manifests.into_iter().zip(documents) {
+        update_manifest(&mut document, &packages, &version);
+        changed.push((manifest, document.to_string()));
+    }
+
+    let lock = base.join("Cargo.lock");
+    if lock.exists() {
+        let mut document: Document = fs::read_to_string(&lock)?.parse()?;
+        update_lock(&mut document, &packages, &version);
+        changed.push((lock, document.to_string()));
+    }
+
+    let tag = format!("{}{}", prefix.unwrap_or("v"), version);
+    if dry_run {
+        for (path, _) in &changed {
+            log::info!("Would update {}", path.display());
+        }
+        log::info!("Would commit and create tag {}", tag);
+        return Ok(version);
+    }
+
+    for (path, content) in &changed {
+        fs::write(path, content)?;
+    }
+    let paths: Vec<PathBuf> = changed.into_iter().map(|(path, _)| path).collect();
+    git::commit_and_tag(
+        base,
+        &paths,
+        &format!("Bump version to {}", tag),
+        &tag,
+        &format!("Release {}", tag),
+    )?;
+    log::info!("Created tag {}", tag);
+
+    if let Some(remote) = push {
+        log::info!("Pushing {} to {}", tag, remote);
+        git::push(base, remote, &tag)?;
+    }
+
+    Ok(version)
+}
+
+/// The root manifest followed by the manifests of the workspace members
+fn manifests(base: &Path) -> Result<Vec<PathBuf>> {
+    let root = base.join("Cargo.toml");
+    let document: Document = fs::read_to_string(&root)
+        .with_context(|| format!("cannot read {}", root.display()))?
+        .parse()?;
+
+    let patterns = |key: &str| -> Vec<String> {
+        document
+            .get("workspace")
+            .and_then(|workspace| workspace.get(key))
+            .and_then(Item::as_array)
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|pattern| pattern.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let exclude: Vec<PathBuf> = patterns("exclude")
+        .iter()
+        .map(|path| base.join(path))
+        .collect();
+
+    let mut manifests = vec![root.to_owned()];
+    for pattern in patterns("members") {
+        let pattern = base.join(pattern);
+        for directory in glob::glob(&pattern.to_string_lossy())? {
+            let directory = directory?;
+            let manifest = directory.join("Cargo.toml");
+            if manifest.exists() && !exclude.contains(&directory) && !manifests.contains(&manifest)
+            {
+                manifests.push(manifest);
+            }
+        }
+    }
+
+    Ok(manifests)
+}
+
+/// Version of the package of `manifest`, or of its workspace
+pub fn package_version(manifest: &Path) -> Result<Version> {
+    let document: Document = fs::read_to_string(manifest)?.parse()?;
+    let version = document
+        .get("package")
+        .and_then(|package| package.get("version"))
+        .or_else(|| {
+            document
+                .get("workspace")
+                .and_then(|workspace| workspace.get("package"))
+                .and_then(|package| package.get("version"))
+        })
+        .and_then(Item::as_str)
+        .with_context(|| format!("no tag nor version found in {}", manifest.display()))?;
+
+    Ok(Version::parse(version)?)
+}
+
+/// Set the version of the package, of the workspace and of the dependencies on
+/// `packages`, inherited versions are left untouched
+fn update_manifest(document: &mut Document, packages: &[String], version: &Version) {
+    if let Some(package) = document.get_mut("package") {
+        set_version(package, version);
+    }
+
+    if let Some(workspace) = document.get_mut("workspace") {
+        if let Some(package) = workspace.get_mut("package") {
+            set_version(package, version);
+        }
+        if let Some(dependencies) = workspace.get_mut("dependencies") {
+            update_dependencies(dependencies, packages, version);
+        }
+    }
+
+    for table in DEPENDENCY_TABLES {
+        if let Some(dependencies) = document.get_mut(table) {
+            update_dependencies(dependencies, packages, version);
+        }
+    }
+}
+
+fn update_dependencies(dependencies: &mut Item, packages: &[String], version: &Version) {
+    if let Some(dependencies) = dependencies.as_table_like_mut() {
+        for (name, dependency) in dependencies.iter_mut() {
+            if packages.iter().any(|package| package == name.get()) {
+                set_version(dependency, version);
+            }
+        }
+    }
+}
+
+fn update_lock(document: &mut Document, packages: &[String], version: &Version) {
+    let Some(locked) = document
+        .get_mut("package")
+        .and_then(Item::as_array_of_tables_mut)
+    else {
+        return;
+    };
+
+    for package in locked.iter_mut() {
+        let local = !package.contains_key("source");
+        let name = package.get("name").and_then(Item::as_str);
+        if local && name.is_some_and(|name| packages.iter().any(|p| p == name)) {
+            package["version"] = toml_edit::value(version.to_string());
+        }
+    }
+}
+
+/// Replace the `version` string of `table`, keeping its formatting
+fn set_version(table: &mut Item, version: &Version) {
+    let Some(current) = table
+        .as_table_like_mut()
+        .and_then(|table| table.get_mut("version"))
+        .and_then(Item::as_value_mut)
+    else {
+        return;
+    };
+
+    if current.is_str() {
+        let decor = current.decor().to_owned();
+        *current = Value::from(version.to_string());
+        *current.decor_mut() = decor;
+    }
+}
@@ -1,7 +1,70 @@
-use anyhow::Result;
-use sha2::{Digest, Sha256};
-use std::{fs::File, io, path::Path};
+use crate::{github::tag::Tag, template};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
 
+const DEFAULT_NAME_TEMPLATE: &str = "checksums.txt";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    Blake3,
+    Sha1,
+}
+
+/// The checksums file listing every asset of a release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumConfig {
+    /// Name of the checksums file, rendered with `version` and `tag`
+    #[serde(default = "ChecksumConfig::default_name_template")]
+    pub name_template: String,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+}
+
+impl Default for ChecksumConfig {
+    fn default() -> Self {
+        ChecksumConfig {
+            name_template: ChecksumConfig::default_name_template(),
+            algorithm: Algorithm::default(),
+        }
+    }
+}
+
+impl ChecksumConfig {
+    fn default_name_template() -> String {
+        DEFAULT_NAME_TEMPLATE.to_owned()
+    }
+
+    pub fn file_name(&self, version: &str, tag: &str) -> Result<String> {
+        template::render(&self.name_template, &NameData { version, tag })
+    }
+
+    /// Glob pattern matching the checksums file of any version
+    fn file_pattern(&self) -> Result<Pattern> {
+        const ANY: &str = "\0";
+        let name = Pattern::escape(&self.file_name(ANY, ANY)?).replace(ANY, "*");
+        Ok(Pattern::new(&name)?)
+    }
+}
+
+#[derive(Serialize)]
+struct NameData<'a> {
+    version: &'a str,
+    tag: &'a str,
+}
+
+/// SHA-256 of the file at `path`, the checksum Homebrew expects
 pub fn create<P>(binary_name: &str, path: P) -> Result<String>
 where
     P: AsRef<Path>,
@@ -9,13 +72,139 @@ where
     let path = path.as_ref();
     log::info!("creating checksum for: {}: {}", binary_name, path.display());
 
-    let mut file = File::open(path)?;
+    hash(path, Algorithm::Sha256)
+}
+
+/// Hex encoded `algorithm` hash of the file at `path`
+pub fn hash(path: &Path, algorithm: Algorithm) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+
+    let hash = match algorithm {
+        Algorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            hex::encode(hasher.finalize())
+        }
+        Algorithm::Sha512 => {
+            let mut hasher = Sha512::new();
+            io::copy(&mut file, &mut hasher)?;
+            hex::encode(hasher.finalize())
+        }
+        Algorithm::Sha1 => {
+            let mut hasher = sha1::Sha1::new();
+            io::copy(&mut file, &mut hasher)?;
+            hex::encode(hasher.finalize())
+        }
+        Algorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut file, &mut hasher)?;
+            hasher.finalize().to_hex().to_string()
+        }
+    };
+
+    Ok(hash)
+}
+
+/// Write the `sha256sum` style checksums of `files`, given as name and path,
+/// to `output_path`
+pub fn write_manifest(
+    files: &[(&str, &Path)],
+    algorithm: Algorithm,
+    output_path: &Path,
+) -> Result<()> {
+    let mut manifest = String::new();
+    for (name, path) in files {
+        manifest.push_str(&format!("{}  {}\n", hash(path, algorithm)?, name));
+    }
+    fs::write(output_path, manifest)?;
+    Ok(())
+}
+
+/// The hash and the name of every file listed in a checksums file
+pub fn parse_manifest(content: &str) -> Result<Vec<(&str, &str)>> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split_once("  ")
+                .with_context(|| format!("invalid line: {}", line))
+        })
+        .collect()
+}
+
+/// The checksums file of `dir` named by `config`, for the `tag` when given or
+/// for any version otherwise, which must then be the only match
+pub fn find_manifest(dir: &Path, config: &ChecksumConfig, tag: Option<&Tag>) -> Result<PathBuf> {
+    if let Some(tag) = tag {
+        let path = dir.join(config.file_name(tag.value(), tag.name())?);
+        if !path.is_file() {
+            bail!("no checksums file {} found", path.display());
+        }
+        return Ok(path);
+    }
+
+    let pattern = config.file_pattern()?;
+    let mut manifests = vec![];
+    for entry in fs::read_dir(dir).with_context(|| format!("cannot read {}", dir.display()))? {
+        let path = entry?.path();
+        let matches = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| pattern.matches(name));
+        if path.is_file() && matches {
+            manifests.push(path);
+        }
+    }
+
+    match manifests.len() {
+        1 => Ok(manifests.remove(0)),
+        0 => bail!(
+            "no checksums file matching {} found in {}",
+            pattern,
+            dir.display()
+        ),
+        _ => bail!(
+            "several checksums files match {} in {}, pick one with --checksums or --tag",
+            pattern,
+            dir.display()
+        ),
+    }
+}
+
+/// Check every file listed in the checksums file at `manifest` against the
+/// files of its directory
+pub fn verify(manifest: &Path, algorithm: Algorithm) -> Result<()> {
+    let dir = manifest.parent().unwrap_or(Path::new("."));
+    let content = fs::read_to_string(manifest)
+        .with_context(|| format!("cannot read {}", manifest.display()))?;
 
-    let mut hasher = Sha256::new();
-    let _ = io::copy(&mut file, &mut hasher)?;
-    let hash = hasher.finalize();
+    let entries =
+        parse_manifest(&content).with_context(|| format!("cannot read {}", manifest.display()))?;
 
-    let encoded = hex::encode(hash);
+    let mut failed = vec![];
+    for (expected, name) in entries {
+        match hash(&dir.join(name), algorithm) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => {
+                log::info!("{}: OK", name);
+            }
+            Ok(_) => {
+                log::error!("{}: FAILED", name);
+                failed.push(name);
+            }
+            Err(err) => {
+                log::error!("{}: {:#}", name, err);
+                failed.push(name);
+            }
+        }
+    }
 
-    Ok(encoded)
+    if !failed.is_empty() {
+        bail!(
+            "{} of the files listed in {} do not match: {}",
+            failed.len(),
+            manifest.display(),
+            failed.join(", ")
+        );
+    }
+    Ok(())
 }
@@ -1,19 +1,127 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use crate::{bump::Bump, checksum::Algorithm};
+use clap::{Args, Parser, Subcommand};
 #[derive(Parser, Debug)]
 #[clap(about, author, version, name = "rust-releaser")]
 pub struct Opts {
-    /// Path to the project
-    #[clap(default_value = ".")]
-    pub path: PathBuf,
+    #[clap(subcommand)]
+    pub command: Command,
     /// Path to the config file
-    #[clap(short, long, default_value = "releaser.toml")]
+    #[clap(short, long, global = true, default_value = "releaser.toml")]
     pub config: String,
     /// Dry run (do not upload anything)
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     pub dry_run: bool,
     /// Output directory for temporary files
-    #[clap(short, long, default_value = ".")]
+    #[clap(short, long, global = true, default_value = ".")]
     pub output: PathBuf,
+    /// Tag to release instead of the one pointing at HEAD
+    #[clap(short, long, global = true)]
+    pub tag: Option<String>,
+    /// Build and package HEAD into the output directory with a snapshot
+    /// version, without a tag and without publishing anything
+    #[clap(long, global = true, conflicts_with_all = ["tag", "nightly"])]
+    pub snapshot: bool,
+    /// Replace the rolling nightly GitHub release with a snapshot of HEAD
+    #[clap(long, global = true, conflicts_with = "tag")]
+    pub nightly: bool,
+    /// Skip the checks of the repository state before releasing
+    #[clap(long, global = true)]
+    pub skip_validate: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run every stage: build, GitHub release, Homebrew formula and crates.io
+    Release {
+        #[clap(flatten)]
+        project: ProjectOpts,
+        #[clap(flatten)]
+        build: BuildOpts,
+    },
+    /// Build the binaries for every configured target
+    Build {
+        #[clap(flatten)]
+        project: ProjectOpts,
+        #[clap(flatten)]
+        build: BuildOpts,
+    },
+    /// Package the built binaries and upload them to a GitHub release
+    Github {
+        #[clap(flatten)]
+        project: ProjectOpts,
+    },
+    /// Render the Homebrew formula and push it to the tap
+    Brew {
+        #[clap(flatten)]
+        project: ProjectOpts,
+    },
+    /// Publish the configured packages to crates.io
+    Publish {
+        #[clap(flatten)]
+        project: ProjectOpts,
+    },
+    /// Check the config, the current tag and the built binaries
+    Check {
+        #[clap(flatten)]
+        project: ProjectOpts,
+    },
+    /// Bump the version of the workspace, commit it and create the release tag
+    Bump {
+        /// Part of the version to increment
+        #[clap(value_enum, default_value = "auto")]
+        bump: Bump,
+        #[clap(flatten)]
+        project: ProjectOpts,
+        /// Push the commit and the tag to this remote
+        #[clap(long, num_args = 0..=1, default_missing_value = "origin")]
+        push: Option<String>,
+    },
+    /// Check the files of a directory against its checksums file
+    Verify {
+        /// Directory holding the release assets
+        #[clap(default_value = ".")]
+        dir: PathBuf,
+        /// Checksums file, the one named by `release.checksum.name_template` for
+        /// `--tag`, or the only one of any version, by default
+        #[clap(long)]
+        checksums: Option<PathBuf>,
+        /// Hash algorithm of the checksums file [default: the configured one]
+        #[clap(long, value_enum)]
+        algorithm: Option<Algorithm>,
+    },
+}
+
+impl Command {
+    pub fn project(&self) -> Option<&ProjectOpts> {
+        let project = match self {
+            Command::Release { project, .. }
+            | Command::Build { project, .. }
+            | Command::Github { project }
+            | Command::Brew { project }
+            | Command::Publish { project }
+            | Command::Check { project }
+            | Command::Bump { project, .. } => project,
+            Command::Verify { .. } => return None,
+        };
+        Some(project)
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BuildOpts {
+    /// Number of targets built at the same time [default: number of cpus]
+    #[clap(short, long)]
+    pub parallelism: Option<usize>,
+    /// Keep building the other targets when one fails, and release only the successful ones
+    #[clap(short, long)]
+    pub keep_going: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ProjectOpts {
+    /// Path to the project
+    #[clap(default_value = ".")]
+    pub path: PathBuf,
 }
@@ -1,11 +1,18 @@
 use crate::{
     brew::{install::Install, repository::Repository},
-    build::Build,
+    build::{binary::Binaries, Build},
+    checksum::ChecksumConfig,
+    git::{self, archive::SourceConfig, changelog::ChangelogConfig, validate::ValidateConfig},
+    metadata::Metadata,
+    sign::SignConfig,
+    snapshot::SnapshotConfig,
 };
 use anyhow::Result;
 use config::Config;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 const MAIN_BRANCH_NAME: &str = "main";
+const ORIGIN: &str = "origin";
 const BREW_DEFAULT_COMMIT_MESSAGE: &str = "update formula";
 
 const PR_DEFAULT_BASE_BRANCH_NAME: &str = MAIN_BRANCH_NAME;
@@ -13,22 +20,123 @@ const PR_DEFAULT_HEAD_BRANCH_NAME: &str = "bumps-formula-version";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReleaserConfig {
+    #[serde(default)]
     pub build: Build,
     pub brew: Option<BrewConfig>,
+    #[serde(default)]
     pub release: ReleaseConfig,
     pub crates_io: Option<CratesIoConfig>,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
 }
 
 impl ReleaserConfig {
+    /// Load the config file at `path`, a missing one is empty so that the
+    /// defaults and what [`ReleaserConfig::detect`] finds fill every field
     pub async fn load(path: String) -> Result<Self> {
+        if !Path::new(&path).exists() {
+            log::debug!("no config file at {}, using the defaults", path);
+        }
         let config = Config::builder()
-            .add_source(config::File::with_name(&path))
+            .add_source(config::File::with_name(&path).required(false))
             .add_source(config::Environment::with_prefix("RELEASER"))
             .build()?
             .try_deserialize()?;
 
         Ok(config)
     }
+
+    /// Fill in what the config leaves out from the origin remote and the
+    /// cargo metadata of the project at `base`
+    pub fn detect(&mut self, base: &Path) -> Result<()> {
+        let release = &mut self.release;
+        if release.owner.is_empty() || release.repo.is_empty() {
+            match git::github_repository(base, ORIGIN) {
+                Ok(Some((owner, repo))) => {
+                    log::debug!("detected GitHub repository {}/{}", owner, repo);
+                    if release.owner.is_empty() {
+                        release.owner = owner;
+                    }
+                    if release.repo.is_empty() {
+                        release.repo = repo;
+                    }
+                }
+                Ok(None) => log::debug!("{} is not a GitHub remote", ORIGIN),
+                Err(err) => log::debug!("cannot read the {} remote: {}", ORIGIN, err),
+            }
+        }
+        if release.target_branch.is_empty() {
+            release.target_branch = git::current_branch(base)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| MAIN_BRANCH_NAME.to_owned());
+        }
+
+        let brew_incomplete = self.brew.as_ref().is_some_and(|brew| {
+            brew.name.is_empty()
+                || brew.description.is_empty()
+                || brew.homepage.is_empty()
+                || brew.license.is_empty()
+        });
+        if !self.build.binaries.is_empty() && !brew_incomplete {
+            return Ok(());
+        }
+
+        let metadata = Metadata::load(base)?;
+        if self.build.binaries.is_empty() {
+            self.build.binaries = Binaries(metadata.binaries(base));
+            log::debug!("detected binaries: {:?}", self.build.binaries.names());
+        }
+
+        if let Some(brew) = &mut self.brew {
+            let first = self.build.binaries.iter().next();
+            let package = metadata.root_package(base).or_else(|| {
+                let name = first.and_then(|binary| binary.package.as_ref())?;
+                metadata.packages.iter().find(|p| &p.name == name)
+            });
+            if brew.name.is_empty() {
+                brew.name = package
+                    .map(|package| package.name.to_owned())
+                    .or_else(|| first.map(|binary| binary.name.to_owned()))
+                    .unwrap_or_default();
+            }
+            if let Some(package) = package {
+                if brew.description.is_empty() {
+                    brew.description = package.description.to_owned().unwrap_or_default();
+                }
+                if brew.license.is_empty() {
+                    brew.license = package.license.to_owned().unwrap_or_default();
+                }
+                if brew.homepage.is_empty() {
+                    brew.homepage = package
+                        .homepage
+                        .as_ref()
+                        .or(package.repository.as_ref())
+                        .cloned()
+                        .unwrap_or_default();
+                }
+            }
+            if brew.homepage.is_empty() && !self.release.repo.is_empty() {
+                brew.homepage = format!(
+                    "https://github.com/{}/{}",
+                    self.release.owner, self.release.repo
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GitConfig {
+    /// Text before the version in the release tags, e.g. `cli-v` or `server/`.
+    /// Tags are the version with an optional `v` when not set
+    pub tag_prefix: Option<String>,
+    #[serde(default)]
+    pub validate: ValidateConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,12 +150,15 @@ pub struct CratesIoConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrewConfig {
+    /// Name of the formula, the name of the package by default
+    #[serde(default)]
     pub name: String,
+    /// Defaults to the description of the package, as do `homepage` and `license`
     #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub homepage: String,
-    pub install: Install,
+    pub install: Option<Install>,
     #[serde(default)]
     pub license: String,
     #[serde(default = "BrewConfig::main_branch_name")]
@@ -104,14 +215,31 @@ impl PullRequestConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ReleaseConfig {
+    /// Owner and name of the GitHub repository, the `origin` remote by default
+    #[serde(default)]
     pub owner: String,
+    #[serde(default)]
     pub repo: String,
+    /// Branch the release tag is created on, the current branch by default
+    #[serde(default)]
     pub target_branch: String,
     #[serde(default)]
     pub prerelease: bool,
     #[serde(default)]
     pub draft: bool,
+    /// Template of the release notes rendered with `version` and `changelog`,
+    /// the changelog alone by default
     pub body: Option<String>,
+    #[serde(default)]
+    pub changelog: ChangelogConfig,
+    #[serde(default)]
+    pub checksum: ChecksumConfig,
+    pub sign: Option<SignConfig>,
+    /// Upload a CycloneDX SBOM next to each archive
+    #[serde(default)]
+    pub sbom: bool,
+    #[serde(default)]
+    pub source: SourceConfig,
 }
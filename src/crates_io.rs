@@ -0,0 +1,36 @@
+use crate::config::CratesIoConfig;
+use anyhow::{bail, Result};
+use std::path::Path;
+use tokio::process::Command;
+
+pub async fn publish(crates_io: &CratesIoConfig, path: &Path, dry_run: bool) -> Result<()> {
+    for package in &crates_io.packages {
+        log::info!("Publishing {} to crates.io", package);
+        let mut cmd = Command::new("cargo");
+        cmd.arg("publish").current_dir(path);
+        if crates_io.allow_dirty.unwrap_or(false) {
+            cmd.arg("--allow-dirty");
+        }
+        if crates_io.no_verify.unwrap_or(false) {
+            cmd.arg("--no-verify");
+        }
+        if let Some(registry) = &crates_io.registry {
+            cmd.arg("--registry").arg(registry);
+        }
+        if let Some(index) = &crates_io.index {
+            cmd.arg("--index").arg(index);
+        }
+        cmd.arg("--package").arg(package);
+
+        if dry_run {
+            log::info!("Would run: {:?}", cmd);
+        } else {
+            let status = cmd.status().await?;
+            if !status.success() {
+                bail!("failed to publish {} to crates.io: {}", package, status);
+            }
+        }
+    }
+
+    Ok(())
+}
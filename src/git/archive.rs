@@ -0,0 +1,240 @@
+use crate::{github::tag::Tag, template};
+use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
+use git2::{ObjectType, Oid, Repository, Tree};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tar::{Builder, EntryType, Header};
+
+const DEFAULT_NAME_TEMPLATE: &str = "{{name}}-{{version}}";
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+const EXPORT_IGNORE: &str = "export-ignore";
+const SYMLINK_MODE: i32 = 0o120000;
+const EXECUTABLE_MODE: i32 = 0o100755;
+
+/// The source tarball uploaded with the release and used by the formula
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    #[serde(default)]
+    pub disable: bool,
+    /// Name of the tarball and of its top directory, without the extension,
+    /// rendered with `name`, `version` and `tag`
+    #[serde(default = "SourceConfig::default_name_template")]
+    pub name_template: String,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig {
+            disable: false,
+            name_template: SourceConfig::default_name_template(),
+        }
+    }
+}
+
+impl SourceConfig {
+    fn default_name_template() -> String {
+        DEFAULT_NAME_TEMPLATE.to_owned()
+    }
+
+    pub fn stem(&self, name: &str, tag: &Tag) -> Result<String> {
+        template::render(
+            &self.name_template,
+            &NameData {
+                name,
+                version: tag.value(),
+                tag: tag.name(),
+            },
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct NameData<'a> {
+    name: &'a str,
+    version: &'a str,
+    tag: &'a str,
+}
+
+/// Write a gzipped tarball of the tree of `commit`, with every file under the
+/// `prefix` directory. Paths given the `export-ignore` attribute by the
+/// `.gitattributes` files of that tree are left out like `git archive` does.
+/// Every entry gets the commit time and fixed owners, so the archive only
+/// depends on the tree
+pub fn source_archive(base: &Path, commit: &str, prefix: &str, output_path: &Path) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let commit = repo.find_commit(Oid::from_str(commit)?)?;
+    log::debug!("archiving the tree of {} into {}", commit.id(), prefix);
+
+    let file = File::create(output_path)
+        .with_context(|| format!("cannot create {}", output_path.display()))?;
+    let mut archive = SourceArchive {
+        repo: &repo,
+        builder: Builder::new(GzEncoder::new(file, Compression::default())),
+        mtime: commit.time().seconds().max(0) as u64,
+        rules: vec![],
+    };
+    let prefix = PathBuf::from(prefix);
+    archive.append_directory(&prefix)?;
+    archive.append_tree(&commit.tree()?, Path::new(""), &prefix)?;
+
+    archive.builder.into_inner()?.finish()?.flush()?;
+    Ok(())
+}
+
+struct SourceArchive<'repo, W: Write> {
+    repo: &'repo Repository,
+    builder: Builder<W>,
+    mtime: u64,
+    /// `export-ignore` rules of the `.gitattributes` files of the directories
+    /// being walked, from the root down so that the last match wins
+    rules: Vec<Rule>,
+}
+
+/// A line of a `.gitattributes` file setting or unsetting `export-ignore`
+struct Rule {
+    /// Directory holding the `.gitattributes` file
+    directory: PathBuf,
+    pattern: Pattern,
+    /// Matched against the path relative to `directory` when the pattern has
+    /// a slash, against the file name otherwise
+    anchored: bool,
+    ignore: bool,
+}
+
+impl Rule {
+    fn parse(directory: &Path, line: &str) -> Option<Rule> {
+        let mut fields = line.split_whitespace();
+        let pattern = fields.next().filter(|p| !p.starts_with('#'))?;
+        // the last setting of the attribute on the line wins
+        let ignore = fields
+            .filter_map(|attribute| match attribute {
+                EXPORT_IGNORE => Some(true),
+                _ => attribute
+                    .strip_prefix(['-', '!'])
+                    .filter(|name| *name == EXPORT_IGNORE)
+                    .map(|_| false),
+            })
+            .next_back()?;
+
+        // negative patterns are forbidden in attributes files
+        if pattern.starts_with('!') {
+            return None;
+        }
+        let anchored = pattern.contains('/');
+        let pattern = Pattern::new(pattern.trim_start_matches('/')).ok()?;
+        Some(Rule {
+            directory: directory.to_path_buf(),
+            pattern,
+            anchored,
+            ignore,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.directory) else {
+            return false;
+        };
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        if self.anchored {
+            self.pattern.matches_path_with(relative, options)
+        } else {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.pattern.matches_with(name, options))
+        }
+    }
+}
+
+impl<W: Write> SourceArchive<'_, W> {
+    /// Append the entries of `tree`, found at `path` in the repository, under
+    /// `directory` in the archive
+    fn append_tree(&mut self, tree: &Tree, path: &Path, directory: &Path) -> Result<()> {
+        let inherited = self.rules.len();
+        if let Some(attributes) = tree.get_name(ATTRIBUTES_FILE) {
+            let blob = self.repo.find_blob(attributes.id())?;
+            let content = String::from_utf8_lossy(blob.content());
+            self.rules
+                .extend(content.lines().filter_map(|line| Rule::parse(path, line)));
+        }
+
+        for entry in tree.iter() {
+            let name = entry.name().context("non utf-8 path in the repository")?;
+            let path = path.join(name);
+            if self.export_ignored(&path) {
+                log::debug!("leaving out {} (export-ignore)", path.display());
+                continue;
+            }
+            let target = directory.join(name);
+
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    self.append_directory(&target)?;
+                    let tree = self.repo.find_tree(entry.id())?;
+                    self.append_tree(&tree, &path, &target)?;
+                }
+                Some(ObjectType::Blob) => {
+                    let blob = self.repo.find_blob(entry.id())?;
+                    let mut header = self.header(EntryType::Regular);
+                    match entry.filemode() {
+                        SYMLINK_MODE => {
+                            header.set_entry_type(EntryType::Symlink);
+                            header.set_mode(0o777);
+                            let link = String::from_utf8_lossy(blob.content()).to_string();
+                            self.builder.append_link(&mut header, &target, link)?;
+                        }
+                        mode => {
+                            if mode == EXECUTABLE_MODE {
+                                header.set_mode(0o755);
+                            }
+                            header.set_size(blob.content().len() as u64);
+                            self.builder
+                                .append_data(&mut header, &target, blob.content())?;
+                        }
+                    }
+                }
+                // submodules are not part of the tree
+                _ => {}
+            }
+        }
+
+        self.rules.truncate(inherited);
+        Ok(())
+    }
+
+    fn append_directory(&mut self, target: &Path) -> Result<()> {
+        let mut header = self.header(EntryType::Directory);
+        header.set_mode(0o755);
+        self.builder
+            .append_data(&mut header, target, std::io::empty())?;
+        Ok(())
+    }
+
+    fn header(&self, entry_type: EntryType) -> Header {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_mode(0o644);
+        header.set_mtime(self.mtime);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_size(0);
+        header
+    }
+
+    fn export_ignored(&self, path: &Path) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path))
+            .is_some_and(|rule| rule.ignore)
+    }
+}
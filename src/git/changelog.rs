@@ -0,0 +1,231 @@
+use super::{peel, reachable_tags, semver_tags};
+use crate::github::tag::Tag;
+use anyhow::{Context, Result};
+use git2::{Oid, Repository, Sort};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const BREAKING_CHANGES_TITLE: &str = "Breaking changes";
+const OTHER_CHANGES_TITLE: &str = "Other changes";
+
+/// `type(scope)!: description`
+static CONVENTIONAL_COMMIT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<type>[a-zA-Z]+)(\((?P<scope>[^)]*)\))?(?P<breaking>!)?: *(?P<description>.+)$",
+    )
+    .expect("invalid conventional commit regex")
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogConfig {
+    #[serde(default)]
+    pub disable: bool,
+    /// Regexes, when set only the commits whose subject matches one of them are listed
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Regexes of the commit subjects left out of the changelog
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Sections of the changelog, breaking changes always come first
+    #[serde(default = "ChangelogConfig::default_groups")]
+    pub groups: Vec<ChangelogGroup>,
+    /// Title of the section of the commits in no group, they are left out when empty
+    #[serde(default = "ChangelogConfig::default_other")]
+    pub other: String,
+}
+
+/// Section listing the commits of the given Conventional Commit types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogGroup {
+    pub title: String,
+    pub types: Vec<String>,
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        ChangelogConfig {
+            disable: false,
+            include: vec![],
+            exclude: vec![],
+            groups: ChangelogConfig::default_groups(),
+            other: ChangelogConfig::default_other(),
+        }
+    }
+}
+
+impl ChangelogConfig {
+    fn default_groups() -> Vec<ChangelogGroup> {
+        [
+            ("Features", "feat"),
+            ("Bug fixes", "fix"),
+            ("Performance", "perf"),
+        ]
+        .into_iter()
+        .map(|(title, kind)| ChangelogGroup {
+            title: title.to_string(),
+            types: vec![kind.to_string()],
+        })
+        .collect()
+    }
+
+    fn default_other() -> String {
+        OTHER_CHANGES_TITLE.to_owned()
+    }
+}
+
+#[derive(Debug)]
+pub struct Commit {
+    pub subject: String,
+    /// Conventional Commit type, lowercased
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+    pub id: String,
+}
+
+impl Commit {
+    fn parse(commit: &git2::Commit) -> Commit {
+        let subject = commit.summary().unwrap_or_default().trim().to_string();
+        let breaking_footer = commit
+            .body()
+            .is_some_and(|body| body.contains("BREAKING CHANGE"));
+        let id = commit.id().to_string()[..7].to_string();
+
+        match CONVENTIONAL_COMMIT.captures(&subject) {
+            Some(captures) => Commit {
+                subject: subject.to_owned(),
+                kind: Some(captures["type"].to_lowercase()),
+                scope: captures
+                    .name("scope")
+                    .map(|scope| scope.as_str().to_string())
+                    .filter(|scope| !scope.is_empty()),
+                breaking: captures.name("breaking").is_some() || breaking_footer,
+                description: captures["description"].to_string(),
+                id,
+            },
+            None => Commit {
+                kind: None,
+                scope: None,
+                breaking: breaking_footer,
+                description: subject.to_owned(),
+                subject,
+                id,
+            },
+        }
+    }
+
+    fn line(&self) -> String {
+        match &self.scope {
+            Some(scope) => format!("- **{}:** {} ({})", scope, self.description, self.id),
+            None => format!("- {} ({})", self.description, self.id),
+        }
+    }
+}
+
+/// Markdown changelog of the commits between the semver tag before `tag` and `tag`
+pub fn generate(base: &Path, tag: &Tag, config: &ChangelogConfig) -> Result<String> {
+    if config.disable {
+        return Ok(String::new());
+    }
+
+    let include = compile(&config.include)?;
+    let exclude = compile(&config.exclude)?;
+
+    let repo = Repository::open(base)?;
+    let version = Version::parse(tag.value())?;
+    let tags = semver_tags(&repo, Some(tag.prefix()))?;
+    // a tag given on the command line may not exist yet
+    let current = match tags.iter().find(|(v, _)| v == &version) {
+        Some((_, name)) => peel(&repo, name)?,
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+    let previous = tags
+        .iter()
+        .rev()
+        .find(|(v, _)| v < &version)
+        .map(|(_, name)| name);
+    log::debug!("changelog from {:?} to {}", previous, current);
+
+    let commits: Vec<Commit> = walk(&repo, previous.map(String::as_str), current)?
+        .into_iter()
+        .filter(|commit| {
+            include.is_empty() || include.iter().any(|regex| regex.is_match(&commit.subject))
+        })
+        .filter(|commit| !exclude.iter().any(|regex| regex.is_match(&commit.subject)))
+        .collect();
+
+    let mut sections: Vec<(&str, Vec<&Commit>)> = vec![(BREAKING_CHANGES_TITLE, vec![])];
+    sections.extend(config.groups.iter().map(|g| (g.title.as_str(), vec![])));
+    sections.push((config.other.as_str(), vec![]));
+
+    for commit in &commits {
+        let index = if commit.breaking {
+            0
+        } else {
+            config
+                .groups
+                .iter()
+                .position(|group| {
+                    commit
+                        .kind
+                        .as_ref()
+                        .is_some_and(|kind| group.types.contains(kind))
+                })
+                .map_or(sections.len() - 1, |index| index + 1)
+        };
+        sections[index].1.push(commit);
+    }
+
+    Ok(sections
+        .into_iter()
+        .filter(|(title, commits)| !title.is_empty() && !commits.is_empty())
+        .map(|(title, commits)| {
+            let lines = commits.iter().map(|commit| commit.line()).join("\n");
+            format!("## {}\n\n{}\n", title, lines)
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Commits of HEAD since the latest semver tag
+pub fn unreleased(base: &Path, prefix: Option<&str>) -> Result<Vec<Commit>> {
+    let repo = Repository::open(base)?;
+    let latest = reachable_tags(&repo, prefix)?.pop().map(|(_, name)| name);
+    let head = repo.head()?.peel_to_commit()?.id();
+    walk(&repo, latest.as_deref(), head)
+}
+
+/// Non merge commits reachable from `to` but not from the `from` tag, newest first
+fn walk(repo: &Repository, from: Option<&str>, to: Oid) -> Result<Vec<Commit>> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push(to)?;
+    if let Some(from) = from {
+        walk.hide(peel(repo, from)?)?;
+    }
+
+    let mut commits = vec![];
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        commits.push(Commit::parse(&commit));
+    }
+
+    Ok(commits)
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).with_context(|| format!("invalid changelog regex: {}", pattern))
+        })
+        .collect()
+}
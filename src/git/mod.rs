@@ -1,27 +1,288 @@
-use std::path::PathBuf;
+pub mod archive;
+pub mod changelog;
+pub mod validate;
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use crate::github::tag::Tag;
-use anyhow::{bail, Result};
-use git2::Repository;
+use anyhow::{bail, Context, Result};
+use git2::{
+    Cred, CredentialType, DescribeOptions, Oid, PushOptions, RemoteCallbacks, Repository,
+    StatusOptions,
+};
 use itertools::Itertools;
 use log::debug;
 use semver::Version;
 
-pub fn get_current_tag(base: &PathBuf) -> Result<Tag> {
+/// The release tag: `tag` when given, the semver tag pointing at HEAD otherwise.
+/// Without a `prefix` tags may start with a `v`
+pub fn resolve_tag(base: &Path, tag: Option<&str>, prefix: Option<&str>) -> Result<Tag> {
+    let Some(tag) = tag else {
+        return get_current_tag(base, prefix);
+    };
+
+    let version = match prefix {
+        Some(prefix) => tag.strip_prefix(prefix).unwrap_or(tag),
+        None => tag.trim_start_matches('v'),
+    };
+    let version = Version::parse(version)
+        .with_context(|| format!("tag {} is not a semver version", tag))?
+        .to_string();
+    let name = match prefix {
+        Some(prefix) => format!("{}{}", prefix, version),
+        None => tag.to_string(),
+    };
+
+    Ok(Tag::new(name, version))
+}
+
+/// The semver tag pointing at HEAD, the highest one if there are several
+pub fn get_current_tag(base: &Path, prefix: Option<&str>) -> Result<Tag> {
     let repo = Repository::open(base)?;
+    let head = repo.head()?.peel_to_commit()?.id();
 
-    let binding = repo
-        .tag_names(None)?
+    let tag = semver_tags(&repo, prefix)?
         .into_iter()
-        .filter_map(|t| t.map(|t| t.to_string().trim_start_matches("v").to_string()))
-        .filter_map(|t| Version::parse(&t).ok())
-        .sorted_by(|a, b| a.cmp(b));
-    let tag = match binding.last() {
+        .rfind(|(_, name)| peel(&repo, name).is_ok_and(|commit| commit == head));
+    let (version, name) = match tag {
         Some(tag) => tag,
-        None => bail!(anyhow::anyhow!("No tags found")),
+        None => {
+            let nearest = repo
+                .describe(DescribeOptions::new().describe_tags())
+                .and_then(|describe| describe.format(None))
+                .map(|describe| format!("`git describe` gives {}", describe))
+                .unwrap_or_else(|_| "no tags found".to_string());
+            bail!("HEAD is not tagged ({}), tag it or pass --tag", nearest);
+        }
     };
 
-    debug!("tag: {}", tag);
+    debug!("tag: {}", name);
+
+    Ok(Tag::new(name, version.to_string()))
+}
+
+/// Version of the latest semver tag reachable from HEAD, if any
+pub fn latest_version(base: &Path, prefix: Option<&str>) -> Result<Option<Version>> {
+    let repo = Repository::open(base)?;
+    Ok(reachable_tags(&repo, prefix)?
+        .pop()
+        .map(|(version, _)| version))
+}
+
+/// Id of the commit HEAD points at
+pub fn head_commit(base: &Path) -> Result<String> {
+    let repo = Repository::open(base)?;
+    let commit = repo.head()?.peel_to_commit()?.id();
+    Ok(commit.to_string())
+}
+
+/// Id of the commit `tag` points at
+pub fn tag_commit(base: &Path, tag: &Tag) -> Result<String> {
+    let repo = Repository::open(base)?;
+    let commit = peel(&repo, tag.name()).with_context(|| {
+        format!(
+            "tag {} does not exist, create it or pass --snapshot",
+            tag.name()
+        )
+    })?;
+    Ok(commit.to_string())
+}
+
+/// Name of the branch HEAD is on, if any
+pub fn current_branch(base: &Path) -> Result<Option<String>> {
+    let repo = Repository::open(base)?;
+    let head = repo.head()?;
+    Ok(head
+        .shorthand()
+        .filter(|_| head.is_branch())
+        .map(str::to_string))
+}
+
+/// Owner and name of the GitHub repository `remote` points at, if any
+pub fn github_repository(base: &Path, remote: &str) -> Result<Option<(String, String)>> {
+    let repo = Repository::open(base)?;
+    let Ok(remote) = repo.find_remote(remote) else {
+        return Ok(None);
+    };
+    Ok(remote.url().and_then(parse_github_url))
+}
 
-    Ok(Tag::new(tag.to_string()))
+/// `owner/repo` of the https, ssh and scp-like urls of GitHub
+fn parse_github_url(url: &str) -> Option<(String, String)> {
+    let (_, path) = url.split_once("github.com")?;
+    let path = path
+        .trim_start_matches([':', '/'])
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    match path.split('/').collect::<Vec<_>>()[..] {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() => {
+            Some((owner.to_string(), repo.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Tracked files of the repository at `base` with staged or unstaged changes
+pub fn dirty_files(base: &Path) -> Result<Vec<String>> {
+    let repo = Repository::open(base)?;
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+/// Commit the tracked `paths` on top of HEAD and point the annotated `tag` at
+/// the new commit, untracked ones are left out of it
+pub fn commit_and_tag(
+    base: &Path,
+    paths: &[PathBuf],
+    message: &str,
+    tag: &str,
+    tag_message: &str,
+) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let workdir = repo
+        .workdir()
+        .context("cannot commit in a bare repository")?
+        .canonicalize()?;
+
+    let mut index = repo.index()?;
+    for path in paths {
+        let path = path.canonicalize()?;
+        let path = path.strip_prefix(&workdir)?;
+        if index.get_path(path, 0).is_none() {
+            debug!("not committing untracked {}", path.display());
+            continue;
+        }
+        index.add_path(path)?;
+    }
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let signature = repo
+        .signature()
+        .context("set user.name and user.email in the git config")?;
+    let parent = repo.head()?.peel_to_commit()?;
+    let commit = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&parent],
+    )?;
+    debug!("commit: {}", commit);
+
+    repo.tag(
+        tag,
+        &repo.find_object(commit, None)?,
+        &signature,
+        tag_message,
+        false,
+    )?;
+
+    Ok(())
+}
+
+/// Push the current branch and `tag` to `remote`
+pub fn push(base: &Path, remote: &str, tag: &str) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let config = repo.config()?;
+    let head = repo.head()?;
+    let branch = head
+        .name()
+        .filter(|_| head.is_branch())
+        .context("HEAD is not on a branch")?;
+
+    let mut callbacks = remote_callbacks(&config);
+    callbacks.push_update_reference(|reference, status| match status {
+        Some(status) => Err(git2::Error::from_str(&format!(
+            "{} was rejected: {}",
+            reference, status
+        ))),
+        None => Ok(()),
+    });
+
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    repo.find_remote(remote)?.push(
+        &[
+            format!("{0}:{0}", branch),
+            format!("refs/tags/{0}:refs/tags/{0}", tag),
+        ],
+        Some(&mut options),
+    )?;
+
+    Ok(())
+}
+
+/// Callbacks authenticating with the ssh agent, `GITHUB_TOKEN` or the git
+/// credential helper
+fn remote_callbacks(config: &git2::Config) -> RemoteCallbacks<'_> {
+    let mut attempts = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts += 1;
+        if attempts > 3 {
+            return Err(git2::Error::from_str("no valid credentials found"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Ok(token) = env::var("GITHUB_TOKEN") {
+                return Cred::userpass_plaintext("x-access-token", &token);
+            }
+            return Cred::credential_helper(config, url, username);
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Semver tags of the history of HEAD, sorted from the oldest version
+fn reachable_tags(repo: &Repository, prefix: Option<&str>) -> Result<Vec<(Version, String)>> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let mut tags = vec![];
+    for (version, name) in semver_tags(repo, prefix)? {
+        let commit = peel(repo, &name)?;
+        if commit == head || repo.graph_descendant_of(head, commit)? {
+            tags.push((version, name));
+        }
+    }
+    Ok(tags)
+}
+
+fn peel(repo: &Repository, tag: &str) -> Result<Oid> {
+    Ok(repo
+        .revparse_single(&format!("refs/tags/{}", tag))?
+        .peel_to_commit()?
+        .id())
+}
+
+/// Every tag of `repo` made of `prefix` and a semver version, or of an
+/// optional `v` and the version without a prefix, sorted from the oldest
+/// version along with its name
+fn semver_tags(repo: &Repository, prefix: Option<&str>) -> Result<Vec<(Version, String)>> {
+    Ok(repo
+        .tag_names(None)?
+        .into_iter()
+        .flatten()
+        .filter_map(|name| {
+            let version = match prefix {
+                Some(prefix) => name.strip_prefix(prefix)?,
+                None => name.trim_start_matches('v'),
+            };
+            Version::parse(version)
+                .ok()
+                .map(|version| (version, name.to_string()))
+        })
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .collect())
 }
@@ -0,0 +1,159 @@
+use super::{dirty_files, peel, remote_callbacks};
+use crate::{build::binary::Binaries, github::tag::Tag, metadata::Metadata};
+use anyhow::{bail, Context, Result};
+use git2::{Direction, Repository};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const DEFAULT_REMOTE: &str = "origin";
+
+/// Checks of the repository run before releasing, each one can be turned off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateConfig {
+    /// Fail when tracked files have uncommitted changes
+    #[serde(default = "ValidateConfig::enabled")]
+    pub clean: bool,
+    /// Fail when the tag does not point at HEAD
+    #[serde(default = "ValidateConfig::enabled")]
+    pub head: bool,
+    /// Fail when the tag is missing from `remote`
+    #[serde(default = "ValidateConfig::enabled")]
+    pub pushed: bool,
+    /// Fail when the version of the package of the binaries is not the one of
+    /// the tag
+    #[serde(default = "ValidateConfig::enabled")]
+    pub version: bool,
+    #[serde(default = "ValidateConfig::default_remote")]
+    pub remote: String,
+}
+
+impl Default for ValidateConfig {
+    fn default() -> Self {
+        ValidateConfig {
+            clean: true,
+            head: true,
+            pushed: true,
+            version: true,
+            remote: ValidateConfig::default_remote(),
+        }
+    }
+}
+
+impl ValidateConfig {
+    fn enabled() -> bool {
+        true
+    }
+
+    fn default_remote() -> String {
+        DEFAULT_REMOTE.to_owned()
+    }
+}
+
+/// Check that the repository at `base` is ready to release `tag`, reporting
+/// every failed check at once
+pub fn validate(
+    base: &Path,
+    tag: &Tag,
+    binaries: &Binaries,
+    config: &ValidateConfig,
+) -> Result<()> {
+    let repo = Repository::open(base)?;
+    let mut errors = vec![];
+
+    if config.clean {
+        let dirty = dirty_files(base)?;
+        if !dirty.is_empty() {
+            errors.push(format!(
+                "the working tree has uncommitted changes: {}",
+                dirty.join(", ")
+            ));
+        }
+    }
+
+    if config.head {
+        let head = repo.head()?.peel_to_commit()?.id();
+        match peel(&repo, tag.name()) {
+            Ok(commit) if commit == head => {}
+            Ok(commit) => errors.push(format!(
+                "tag {} points at {} but HEAD is {}",
+                tag.name(),
+                commit,
+                head
+            )),
+            Err(_) => errors.push(format!("tag {} does not exist", tag.name())),
+        }
+    }
+
+    if config.pushed {
+        match is_pushed(&repo, &config.remote, tag.name()) {
+            Ok(true) => {}
+            Ok(false) => errors.push(format!(
+                "tag {} has not been pushed to {}",
+                tag.name(),
+                config.remote
+            )),
+            Err(err) => errors.push(format!(
+                "cannot check that {} is pushed to {}: {:#}",
+                tag.name(),
+                config.remote,
+                err
+            )),
+        }
+    }
+
+    if config.version {
+        match package_versions(base, binaries) {
+            Ok(versions) => {
+                for (name, version) in versions {
+                    if version != tag.value() {
+                        errors.push(format!(
+                            "{} is at version {} but the tag is {}",
+                            name,
+                            version,
+                            tag.name()
+                        ));
+                    }
+                }
+            }
+            Err(err) => errors.push(format!(
+                "cannot compare the version with the tag: {:#}",
+                err
+            )),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "the repository is not ready for a release:\n- {}\nfix it, turn the check off in [git.validate] or pass --skip-validate",
+            errors.join("\n- ")
+        );
+    }
+    Ok(())
+}
+
+/// Name and version of every package the released binaries are built from
+fn package_versions(base: &Path, binaries: &Binaries) -> Result<Vec<(String, String)>> {
+    let metadata = Metadata::load(base)?;
+    let mut versions: Vec<(String, String)> = vec![];
+    for binary in binaries.iter() {
+        let package = metadata
+            .package_of(base, binary)
+            .with_context(|| format!("no package found for the binary {}", binary.name))?;
+        if !versions.iter().any(|(name, _)| name == &package.name) {
+            versions.push((package.name.to_owned(), package.version.to_owned()));
+        }
+    }
+    Ok(versions)
+}
+
+fn is_pushed(repo: &Repository, remote: &str, tag: &str) -> Result<bool> {
+    let config = repo.config()?;
+    let mut remote = repo.find_remote(remote)?;
+    let connection =
+        remote.connect_auth(Direction::Fetch, Some(remote_callbacks(&config)), None)?;
+    let reference = format!("refs/tags/{}", tag);
+    Ok(connection
+        .list()?
+        .iter()
+        .any(|head| head.name() == reference))
+}
@@ -1,34 +1,23 @@
 use super::asset::Asset;
-use crate::build::{arch::Arch, compression::Compression, os::Os};
+use crate::build::{arch::Arch, compression::Compression, os::Os, target::Target};
 
 #[derive(Debug, Clone)]
 pub struct ArchOsMatrixEntry<'matrix> {
     pub arch: &'matrix Arch,
     pub os: &'matrix Os,
     pub name: String,
+    /// Asset name without the archive extension
+    pub stem: String,
     pub asset: Option<Asset>,
 }
 
 impl<'matrix> ArchOsMatrixEntry<'matrix> {
-    pub fn new(
-        arch: &'matrix Arch,
-        os: &'matrix Os,
-        name: String,
-        tag: &'matrix str,
-        compression: &'matrix Compression,
-    ) -> Self {
-        let name = format!(
-            "{}_{}_{}_{}.{}",
-            name,
-            tag,
-            arch.to_string(),
-            os.to_string(),
-            compression.extension()
-        );
+    pub fn new(target: &'matrix Target, stem: String, compression: &Compression) -> Self {
         Self {
-            arch,
-            os,
-            name,
+            arch: &target.arch,
+            os: &target.os,
+            name: compression.file_name(&stem, &target.os),
+            stem,
             asset: None,
         }
     }
@@ -13,7 +13,7 @@ use super::{
 };
 use crate::{
     build::committer::Committer,
-    get,
+    delete, get,
     github::{macros::Headers, release::Release, request::upsert_file_request::UpsertFileRequest},
     http::HttpClient,
     post, put,
@@ -23,7 +23,7 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use log::debug;
 use mime_guess::from_path;
 use once_cell::sync::Lazy;
-use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use reqwest::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
 use std::{env, path::Path};
 use tokio::{fs::File, io::AsyncReadExt};
 
@@ -38,6 +38,17 @@ pub fn instance() -> &'static GithubClient {
 
 pub struct GithubClient;
 
+/// Url an asset of the release for `tag` is downloaded from
+pub fn asset_url(owner: &str, repo: &str, tag: &Tag, name: &str) -> String {
+    format!(
+        "https://github.com/{}/{}/releases/download/{}/{}",
+        owner,
+        repo,
+        tag.name(),
+        name
+    )
+}
+
 impl GithubClient {
     pub fn repo<S>(&self, owner: S, name: S) -> RepositoryHandler
     where
@@ -61,10 +72,7 @@ impl GithubClient {
         let mut file = File::open(&path).await?;
         let metadata = file.metadata().await?;
         let content_length = metadata.len();
-        let content_type = from_path(&path)
-            .first_or_octet_stream()
-            .as_ref()
-            .to_string();
+        let content_type = from_path(path).first_or_octet_stream().as_ref().to_string();
 
         let url = format!(
             "https://uploads.github.com/repos/{}/{}/releases/{}/assets?name={}",
@@ -84,13 +92,7 @@ impl GithubClient {
 
         debug!("upload asset response: {:#?}", res);
 
-        let asset_url = format!(
-            "https://github.com/{}/{}/releases/download/{}/{}",
-            &owner,
-            &repo,
-            tag.strip_v_prefix(),
-            asset.name
-        );
+        let asset_url = asset_url(&owner, &repo, tag, &asset.name);
         log::debug!("creating uploaded asset");
         let uploaded_asset = self.create_uploaded_asset(asset, asset_url);
 
@@ -109,6 +111,20 @@ impl GithubClient {
         )
     }
 
+    pub(super) async fn download_asset(&self, url: &str) -> Result<Vec<u8>> {
+        let response = HttpClient::new()
+            .get(url)
+            .bearer_auth(GITHUB_TOKEN.to_string())
+            .header(ACCEPT, "application/octet-stream")
+            .header(USER_AGENT, "rustreleaser")
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("cannot download {}", url))?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
     pub(super) async fn get_commit_sha(
         &self,
         owner: impl Into<String>,
@@ -149,6 +165,7 @@ impl GithubClient {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn upsert_file(
         &self,
         owner: &str,
@@ -201,6 +218,7 @@ impl GithubClient {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_pull_request(
         &self,
         owner: &str,
@@ -239,6 +257,7 @@ impl GithubClient {
         Ok(pr)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_release(
         &self,
         owner: &str,
@@ -253,7 +272,7 @@ impl GithubClient {
         let uri = format!("https://api.github.com/repos/{}/{}/releases", owner, repo);
 
         let request = CreateReleaseRequest::new(
-            tag.value().to_owned(),
+            tag.name().to_owned(),
             target_branch.to_owned(),
             release_name.to_owned(),
             body.to_owned(),
@@ -280,14 +299,43 @@ impl GithubClient {
             "https://api.github.com/repos/{}/{}/releases/tags/{}",
             owner,
             repo,
-            tag.value()
+            tag.name()
         );
 
         let response = get!(&uri)?;
 
         let release = serde_json::from_str::<ReleaseResponse>(&response)?;
         debug!("release: {:#?}", release);
-        Ok(Release::new(release.id, owner, repo))
+        Ok(Release::new(release.id, owner, repo).with_assets(release.assets))
+    }
+
+    pub(super) async fn delete_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        release_id: u64,
+    ) -> Result<()> {
+        let uri = format!(
+            "https://api.github.com/repos/{}/{}/releases/{}",
+            owner, repo, release_id
+        );
+
+        delete!(&uri)?;
+
+        Ok(())
+    }
+
+    pub(super) async fn delete_tag(&self, owner: &str, repo: &str, tag: &Tag) -> Result<()> {
+        let uri = format!(
+            "https://api.github.com/repos/{}/{}/git/refs/tags/{}",
+            owner,
+            repo,
+            tag.name()
+        );
+
+        delete!(&uri)?;
+
+        Ok(())
     }
 
     async fn set_pr_assignees(
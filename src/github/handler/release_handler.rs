@@ -29,4 +29,17 @@ impl ReleaseHandler {
             .get_release_by_tag(&self.owner, &self.repo, tag)
             .await
     }
+
+    pub async fn delete(&self, release: &Release) -> Result<()> {
+        github_client::instance()
+            .delete_release(&self.owner, &self.repo, release.id)
+            .await
+    }
+
+    /// Delete the git tag of a release, which deleting the release keeps
+    pub async fn delete_tag(&self, tag: &Tag) -> Result<()> {
+        github_client::instance()
+            .delete_tag(&self.owner, &self.repo, tag)
+            .await
+    }
 }
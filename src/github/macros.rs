@@ -48,6 +48,21 @@ macro_rules! get {
     }};
 }
 
+#[macro_export]
+macro_rules! delete {
+    ($url:expr) => {{
+        use $crate::{github::macros::Headers, http::ResponseHandler};
+
+        $crate::http::HttpClient::new()
+            .delete($url)
+            .default_headers()
+            .send()
+            .await
+            .handle()
+            .await
+    }};
+}
+
 #[macro_export]
 macro_rules! post {
     ($url:expr, $body:expr) => {{
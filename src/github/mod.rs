@@ -14,38 +14,49 @@ use self::{
     release::Release, tag::Tag,
 };
 use crate::{
-    brew::package::Package,
-    build::{arch::Arch, os::Os, Build},
-    checksum,
+    brew::package::{Package, Packages},
+    build::{
+        arch::Arch, archive, binary::Binary, compression::Compression, flags::Flags, os::Os,
+        target::Target, Build,
+    },
+    checksum::{self, Algorithm},
     config::ReleaseConfig,
-    git,
+    git::{self, changelog},
     github::{arch_os_matrix::PushArchOsMatrix, asset::Asset},
+    sbom, sign, template,
 };
-use anyhow::{bail, Result};
-use flate2::{write::GzEncoder, Compression};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
 use std::{
-    fs::{self, File},
+    fs,
     future::Future,
-    io::Write,
     path::{Path, PathBuf},
     vec,
 };
-use tar::Builder;
-
-const SINGLE_TARGET_DIR: &str = "target/release";
 
 pub async fn release(
     build_info: &Build,
     release_info: &ReleaseConfig,
+    tag: &Tag,
+    commit: &str,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
-) -> Result<Vec<Package>> {
+    output_path: &Path,
+) -> Result<Packages> {
+    if release_info.owner.is_empty() || release_info.repo.is_empty() {
+        bail!("no GitHub repository found, set release.owner and release.repo or add a GitHub origin remote");
+    }
+    if build_info.binaries.is_empty() {
+        bail!("no binary to release, set build.binary");
+    }
+
     let packages = if build_info.is_multi_target() {
         log::debug!("Running multi target");
         multi(
             build_info.to_owned(),
             release_info.to_owned(),
+            tag,
+            commit,
             base,
             dry_run,
             output_path,
@@ -56,6 +67,8 @@ pub async fn release(
         single(
             build_info.to_owned(),
             release_info.to_owned(),
+            tag,
+            commit,
             base,
             dry_run,
             output_path,
@@ -66,122 +79,373 @@ pub async fn release(
     Ok(packages)
 }
 
+/// Packages of the release of `tag` published by `rr github`, pointing at the
+/// uploaded assets with the checksums of its checksums file, so that nothing
+/// has to be built or packaged again
+pub async fn published(
+    build_info: &Build,
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    commit: &str,
+    output_path: &Path,
+) -> Result<Packages> {
+    let release = github_client::instance()
+        .repo(&release_info.owner, &release_info.repo)
+        .releases()
+        .get_by_tag(tag)
+        .await
+        .with_context(|| format!("no {} release found, run `rr github` first", tag.name()))?;
+
+    let checksums_name = release_info.checksum.file_name(tag.value(), tag.name())?;
+    let manifest = String::from_utf8(release.download_asset(&checksums_name).await?)?;
+    let checksums = checksum::parse_manifest(&manifest)
+        .with_context(|| format!("cannot read {}", checksums_name))?;
+    let published = Published {
+        release: &release,
+        release_info,
+        tag,
+        checksums: &checksums,
+        output_path,
+    };
+
+    let mut packages = Packages::default();
+    for archive in archive_names(build_info, tag, commit)? {
+        packages.archives.push(
+            published
+                .package(archive.name, archive.os, archive.arch)
+                .await?,
+        );
+    }
+    if !release_info.source.disable {
+        let stem = release_info.source.stem(&release_info.repo, tag)?;
+        let name = format!("{}.{}", stem, Compression::TarGz.extension());
+        packages.source = Some(published.package(name, None, None).await?);
+    }
+    Ok(packages)
+}
+
+struct Published<'a> {
+    release: &'a Release,
+    release_info: &'a ReleaseConfig,
+    tag: &'a Tag,
+    checksums: &'a [(&'a str, &'a str)],
+    output_path: &'a Path,
+}
+
+impl Published<'_> {
+    /// Package of the uploaded asset `name`, which is only downloaded when the
+    /// checksums file does not hold its SHA-256
+    async fn package(&self, name: String, os: Option<Os>, arch: Option<Arch>) -> Result<Package> {
+        if !self.release.assets.iter().any(|asset| asset.name == name) {
+            bail!(
+                "the {} release has no {} asset, run `rr github` first",
+                self.tag.name(),
+                name
+            );
+        }
+
+        let sha256 = match self.release_info.checksum.algorithm {
+            Algorithm::Sha256 => self
+                .checksums
+                .iter()
+                .find(|(_, listed)| *listed == name)
+                .map(|(hash, _)| hash.to_string())
+                .with_context(|| format!("{} is not in the checksums file", name))?,
+            _ => {
+                let path = self.output_path.join(&name);
+                fs::write(&path, self.release.download_asset(&name).await?)?;
+                checksum::create(&name, &path)?
+            }
+        };
+
+        let url = github_client::asset_url(
+            &self.release_info.owner,
+            &self.release_info.repo,
+            self.tag,
+            &name,
+        );
+        Ok(Package::new(name, os, arch, Some(url), sha256))
+    }
+}
+
+/// An archive of the binaries, without the os and arch when building for the
+/// host only
+struct ArchiveName {
+    name: String,
+    os: Option<Os>,
+    arch: Option<Arch>,
+}
+
+/// Every archive of the binaries, named as `rr github` names them
+fn archive_names(build_info: &Build, tag: &Tag, commit: &str) -> Result<Vec<ArchiveName>> {
+    let mut names = vec![];
+    if build_info.is_multi_target() {
+        for target in build_info.targets()? {
+            let compression = build_info.compression(&target);
+            for binaries in build_info.archive.group(&build_info.binaries, &compression) {
+                let stem = build_info.archive.stem(
+                    &binaries[0].name,
+                    tag.value(),
+                    &target,
+                    commit,
+                    true,
+                )?;
+                names.push(ArchiveName {
+                    name: compression.file_name(&stem, &target.os),
+                    os: Some(target.os.to_owned()),
+                    arch: Some(target.arch.to_owned()),
+                });
+            }
+        }
+    } else {
+        let target = Target::current();
+        let compression = build_info.compression(&target);
+        for binaries in build_info.archive.group(&build_info.binaries, &compression) {
+            let stem =
+                build_info
+                    .archive
+                    .stem(&binaries[0].name, tag.value(), &target, commit, false)?;
+            names.push(ArchiveName {
+                name: compression.file_name(&stem, &target.os),
+                os: None,
+                arch: None,
+            });
+        }
+    }
+    Ok(names)
+}
+
+/// Delete the release of `tag` and the tag itself if they exist, so that a
+/// rolling release such as `nightly` is created again on the current commit
+pub async fn delete_release(release_info: &ReleaseConfig, tag: &Tag) -> Result<()> {
+    let releases = github_client::instance()
+        .repo(&release_info.owner, &release_info.repo)
+        .releases();
+    match releases.get_by_tag(tag).await {
+        Ok(release) => {
+            log::info!("Deleting the previous {} release", tag.name());
+            releases.delete(&release).await?;
+        }
+        Err(err) => log::debug!("no {} release to delete: {:?}", tag.name(), err),
+    }
+    releases.delete_tag(tag).await
+}
+
+/// Make sure every binary that is about to be packaged has been built
+pub fn check(build_info: &Build, base: &Path) -> Result<()> {
+    if build_info.is_multi_target() {
+        for target in build_info.targets()? {
+            for binary in build_info.binaries.iter() {
+                check_binary(&build_info.binary_path(base, Some(&target), &binary.name))?;
+            }
+        }
+    } else {
+        for binary in build_info.binaries.iter() {
+            check_binary(&build_info.binary_path(base, None, &binary.name))?;
+        }
+    }
+
+    Ok(())
+}
+
 async fn single(
     build_info: Build,
     release_info: ReleaseConfig,
+    tag: &Tag,
+    commit: &str,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
-) -> Result<Vec<Package>> {
-    // validate binary
-    check_binary(&build_info.binary.to_owned(), None, &base)?;
-
-    let tag = git::get_current_tag(&base)?;
-
-    // calculate full binary name
-    let binary_name = format!(
-        "{}_{}.{}",
-        build_info.binary,
-        tag.value(),
-        build_info.compression.extension()
-    );
-
-    log::debug!("binary name: {}", binary_name);
-
-    // zip binary
-    log::debug!("zipping binary");
-    zip_file(
-        &build_info.binary.to_owned(),
-        &output_path.join(binary_name.to_owned()),
-        base.join(format!("{}/{}", SINGLE_TARGET_DIR, build_info.binary)),
-    )?;
+    output_path: &Path,
+) -> Result<Packages> {
+    let target = Target::current();
+    let os = &target.os;
+    let compression = build_info.compression(&target);
+
+    let mut release_info = release_info;
+    release_info.body = Some(release_body(&release_info, &base, tag)?);
+    if dry_run {
+        log::info!(
+            "Release notes:\n{}",
+            release_info.body.as_deref().unwrap_or_default()
+        );
+    }
 
-    let path = output_path.join(binary_name.to_owned());
+    let mut assets = vec![];
+    let mut stems = vec![];
+    let source = source_asset(&release_info, &base, tag, commit, output_path)?;
+    let mut extra_assets: Vec<Asset> = source.iter().cloned().collect();
+    for binaries in build_info.archive.group(&build_info.binaries, &compression) {
+        let name = &binaries[0].name;
+
+        // validate binaries
+        let mut paths = vec![];
+        for binary in &binaries {
+            let path = build_info.binary_path(&base, None, &binary.name);
+            check_binary(&path)?;
+            paths.push(path);
+        }
+
+        // calculate full binary name
+        let stem = build_info
+            .archive
+            .stem(name, tag.value(), &target, commit, false)?;
+        unique_stem(&mut stems, &stem)?;
+        let binary_name = compression.file_name(&stem, os);
+        extra_assets.extend(debug_asset(&build_info, &paths, os, &stem, output_path)?);
+        extra_assets.extend(sbom_asset(
+            &release_info,
+            &base,
+            &binaries,
+            &target.triple,
+            &build_info.flags(None),
+            &stem,
+            output_path,
+        )?);
 
-    // create an asset
-    log::debug!("creating asset");
-    let mut asset = create_asset(binary_name, path);
+        log::debug!("binary name: {}", binary_name);
 
-    // generate a checksum value
-    log::debug!("generating checksum");
-    let checksum = generate_checksum(&asset)?;
+        // zip binary
+        log::debug!("zipping binary");
+        let entries =
+            build_info
+                .archive
+                .entries(paths, name, tag.value(), &target.triple, &base)?;
+        compression.compress(&entries, &output_path.join(&binary_name))?;
 
-    // add checksum to asset
-    log::debug!("adding checksum to asset");
-    asset.add_checksum(checksum);
+        let path = output_path.join(&binary_name);
+
+        // create an asset
+        log::debug!("creating asset");
+        let mut asset = create_asset(binary_name, path);
+
+        // generate a checksum value
+        log::debug!("generating checksum");
+        let checksum = generate_checksum(&asset)?;
+
+        // add checksum to asset
+        log::debug!("adding checksum to asset");
+        asset.add_checksum(checksum);
+
+        assets.push(asset);
+    }
+
+    let checksums = checksums_asset(
+        assets.iter().chain(&extra_assets),
+        &release_info,
+        tag,
+        output_path,
+    )?;
+    let signatures = signatures(&release_info, &assets, &extra_assets, &checksums, dry_run).await?;
 
     // create release
     log::debug!("creating release");
 
     if dry_run {
-        let package = Package::new(
-            asset.name.to_owned(),
-            None,
-            None,
-            None,
-            asset.checksum.to_owned().unwrap_or_default(),
-        );
-        Ok(vec![package])
+        let archives = assets
+            .iter()
+            .map(|asset| planned_package(&release_info, tag, asset))
+            .collect();
+        Ok(Packages {
+            archives,
+            source: source.map(|asset| planned_package(&release_info, tag, &asset)),
+        })
     } else {
-        let release =
-            get_release(release_info, &tag, do_create_release, get_release_by_tag).await?;
+        let release = get_release(release_info, tag, do_create_release, get_release_by_tag).await?;
 
         // upload to release
         log::debug!("uploading asset");
-        let uploaded_assets = match release.upload_assets(vec![asset], &tag, output_path).await {
+        let uploaded_extra_assets = release.upload_assets(extra_assets, tag).await?;
+        let uploaded_assets = match release.upload_assets(assets, tag).await {
             Ok(uploaded_assets) => uploaded_assets,
             Err(e) => {
                 log::error!("Failed to upload asset {:#?}", e);
                 bail!(anyhow::anyhow!("Failed to upload asset"))
             }
         };
+        release.upload_assets(vec![checksums], tag).await?;
+        release.upload_assets(signatures, tag).await?;
 
         // return a package with the asset url and checksum value
-        let packages: Vec<Package> = uploaded_assets
+        let archives: Vec<Package> = uploaded_assets
             .iter()
             .map(|asset| package_asset(asset, None, None))
             .collect();
-        Ok(packages)
+        Ok(Packages {
+            archives,
+            source: uploaded_source(source.as_ref(), &uploaded_extra_assets),
+        })
     }
 }
 
 async fn multi(
     build_info: Build,
     release_info: ReleaseConfig,
+    tag: &Tag,
+    commit: &str,
     base: PathBuf,
     dry_run: bool,
-    output_path: &PathBuf,
-) -> Result<Vec<Package>> {
-    let tag = git::get_current_tag(&base)?;
+    output_path: &Path,
+) -> Result<Packages> {
+    let mut release_info = release_info;
+    release_info.body = Some(release_body(&release_info, &base, tag)?);
+    if dry_run {
+        log::info!(
+            "Release notes:\n{}",
+            release_info.body.as_deref().unwrap_or_default()
+        );
+    }
 
-    let archs = build_info.arch.unwrap_or_default();
-    let os = build_info.os.unwrap_or_default();
+    let targets = build_info.targets()?;
     let mut matrix: Vec<ArchOsMatrixEntry> = Vec::new();
+    let mut stems = vec![];
+    let source = source_asset(&release_info, &base, tag, commit, output_path)?;
+    let mut extra_assets: Vec<Asset> = source.iter().cloned().collect();
 
-    for arch in &archs {
-        for os in &os {
-            let binary = build_info.binary.to_owned();
-            check_binary(
-                &binary,
-                Some(format!("{}-{}", &arch.to_string(), &os.to_string())),
-                &base,
-            )?;
+    for target in &targets {
+        let compression = build_info.compression(target);
+
+        for binaries in build_info.archive.group(&build_info.binaries, &compression) {
+            let name = binaries[0].name.to_owned();
 
-            let mut entry =
-                ArchOsMatrixEntry::new(arch, os, binary, tag.value(), &build_info.compression);
+            let mut paths = vec![];
+            for binary in &binaries {
+                let path = build_info.binary_path(&base, Some(target), &binary.name);
+                check_binary(&path)?;
+                paths.push(path);
+            }
 
-            let target = format!("{}-{}", &arch.to_string(), &os.to_string());
+            let stem = build_info
+                .archive
+                .stem(&name, tag.value(), target, commit, true)?;
+            unique_stem(&mut stems, &stem)?;
+            let mut entry = ArchOsMatrixEntry::new(target, stem, &compression);
+            extra_assets.extend(debug_asset(
+                &build_info,
+                &paths,
+                &target.os,
+                &entry.stem,
+                output_path,
+            )?);
+            extra_assets.extend(sbom_asset(
+                &release_info,
+                &base,
+                &binaries,
+                &target.triple,
+                &build_info.flags(Some(target)),
+                &entry.stem,
+                output_path,
+            )?);
 
             log::debug!("zipping binary for {}", target);
 
             let entry_name = entry.name.to_owned();
 
             // zip binary
-            zip_file(
-                &build_info.binary.to_owned(),
-                &output_path.join(entry_name.to_owned()),
-                base.join(format!("target/{}/release/{}", target, build_info.binary)),
-            )?;
+            let entries =
+                build_info
+                    .archive
+                    .entries(paths, &name, tag.value(), &target.triple, &base)?;
+            compression.compress(&entries, &output_path.join(&entry_name))?;
 
             // create an asset
             let mut asset = Asset::new(entry.name.to_owned(), output_path.join(&entry_name));
@@ -202,34 +466,50 @@ async fn multi(
         .cloned()
         .filter_map(|entry| entry.asset)
         .collect();
+    let checksums = checksums_asset(
+        assets.iter().chain(&extra_assets),
+        &release_info,
+        tag,
+        output_path,
+    )?;
+    let signatures = signatures(&release_info, &assets, &extra_assets, &checksums, dry_run).await?;
+
     if dry_run {
-        let packages: Vec<Package> = matrix
+        let archives: Vec<Package> = matrix
             .into_iter()
             .map(|entry| {
                 let asset = assets
                     .iter()
                     .find(|asset| asset.name == entry.name)
                     .expect("asset not found");
-                let _ = generate_checksum_asset(asset, output_path)
-                    .expect("failed to generate checksum asset");
                 Package::new(
                     asset.name.to_owned(),
                     Some(entry.os.to_owned()),
                     Some(entry.arch.to_owned()),
-                    None,
+                    Some(github_client::asset_url(
+                        &release_info.owner,
+                        &release_info.repo,
+                        tag,
+                        &asset.name,
+                    )),
                     asset.checksum.to_owned().unwrap_or_default(),
                 )
             })
             .collect();
-        Ok(packages)
+        Ok(Packages {
+            archives,
+            source: source.map(|asset| planned_package(&release_info, tag, &asset)),
+        })
     } else {
-        let release =
-            get_release(release_info, &tag, do_create_release, get_release_by_tag).await?;
+        let release = get_release(release_info, tag, do_create_release, get_release_by_tag).await?;
 
         // upload to release
-        let uploaded_assets = release.upload_assets(assets, &tag, output_path).await?;
+        let uploaded_assets = release.upload_assets(assets, tag).await?;
+        let uploaded_extra_assets = release.upload_assets(extra_assets, tag).await?;
+        release.upload_assets(vec![checksums], tag).await?;
+        release.upload_assets(signatures, tag).await?;
 
-        let packages: Vec<Package> = matrix
+        let archives: Vec<Package> = matrix
             .into_iter()
             .map(|entry| {
                 let asset = uploaded_assets
@@ -241,39 +521,126 @@ async fn multi(
             })
             .collect();
 
-        Ok(packages)
+        Ok(Packages {
+            archives,
+            source: uploaded_source(source.as_ref(), &uploaded_extra_assets),
+        })
     }
 }
 
-fn zip_file(binary_name: &str, output_path: &PathBuf, binary_path: PathBuf) -> Result<()> {
-    let mut file = File::open(binary_path)?;
-    let mut archive = Builder::new(Vec::new());
+/// Fail when two archives get the same name, they would overwrite each other
+fn unique_stem(stems: &mut Vec<String>, stem: &str) -> Result<()> {
+    if stems.iter().any(|other| other == stem) {
+        bail!(
+            "several archives are named {}, add {{{{binary}}}}, {{{{arch}}}} or {{{{os}}}} to `archive.name_template`",
+            stem
+        );
+    }
+    stems.push(stem.to_owned());
+    Ok(())
+}
 
-    archive.append_file(binary_name, &mut file)?;
+/// `<stem>.debug.tar.gz` holding the debug info split from `paths` during the
+/// build, when `post_process.split_debug` is set
+fn debug_asset(
+    build_info: &Build,
+    paths: &[PathBuf],
+    os: &Os,
+    stem: &str,
+    output_path: &Path,
+) -> Result<Option<Asset>> {
+    if !build_info.post_process.split_debug {
+        return Ok(None);
+    }
 
-    let compressed_file = File::create(output_path)?;
-    let mut encoder = GzEncoder::new(compressed_file, Compression::default());
-    encoder.write_all(&archive.into_inner()?)?;
+    let name = format!("{}.debug.{}", stem, Compression::TarGz.extension());
+    let path = output_path.join(&name);
+    log::debug!("archiving debug info into {}", name);
+    Compression::TarGz.compress(&archive::debug_entries(paths, os)?, &path)?;
 
-    encoder.finish()?;
+    let mut asset = create_asset(name, path);
+    asset.add_checksum(generate_checksum(&asset)?);
+    Ok(Some(asset))
+}
 
-    Ok(())
+/// `<stem>.cdx.json`, the CycloneDX SBOM of `binaries` built for `triple`,
+/// when `release.sbom` is set
+fn sbom_asset(
+    release_info: &ReleaseConfig,
+    base: &Path,
+    binaries: &[&Binary],
+    triple: &str,
+    flags: &Flags,
+    stem: &str,
+    output_path: &Path,
+) -> Result<Option<Asset>> {
+    if !release_info.sbom {
+        return Ok(None);
+    }
+
+    let name = format!("{}.cdx.json", stem);
+    let path = output_path.join(&name);
+    log::debug!("writing the SBOM of {} to {}", triple, name);
+    sbom::generate(base, binaries, triple, flags, &path)?;
+
+    let mut asset = create_asset(name, path);
+    asset.add_checksum(generate_checksum(&asset)?);
+    Ok(Some(asset))
 }
 
-fn check_binary(name: &str, target: Option<String>, base: &PathBuf) -> Result<()> {
-    log::debug!("checking binary: {} - {:#?}", name, target);
-    let binary_path = base.join(if let Some(target) = target {
-        format!("target/{}/release/{}", target, name)
-    } else {
-        format!("target/release/{}", name)
-    });
+/// `<name>-<version>.tar.gz`, the tagged tree built locally instead of the
+/// archive GitHub generates, unless `release.source.disable` is set
+fn source_asset(
+    release_info: &ReleaseConfig,
+    base: &Path,
+    tag: &Tag,
+    commit: &str,
+    output_path: &Path,
+) -> Result<Option<Asset>> {
+    if release_info.source.disable {
+        return Ok(None);
+    }
 
+    let stem = release_info.source.stem(&release_info.repo, tag)?;
+    let name = format!("{}.{}", stem, Compression::TarGz.extension());
+    let path = output_path.join(&name);
+    log::debug!("archiving the source of {} into {}", tag.name(), name);
+    git::archive::source_archive(base, commit, &stem, &path)?;
+
+    let mut asset = create_asset(name, path);
+    asset.add_checksum(generate_checksum(&asset)?);
+    Ok(Some(asset))
+}
+
+#[derive(Serialize)]
+struct BodyData<'a> {
+    version: &'a str,
+    changelog: &'a str,
+}
+
+/// Release notes rendered from the `body` template, or the changelog alone
+fn release_body(release_info: &ReleaseConfig, base: &Path, tag: &Tag) -> Result<String> {
+    let changelog = changelog::generate(base, tag, &release_info.changelog)?;
+    match &release_info.body {
+        Some(body) => template::render(
+            body,
+            &BodyData {
+                version: tag.value(),
+                changelog: &changelog,
+            },
+        ),
+        None => Ok(changelog),
+    }
+}
+
+fn check_binary(binary_path: &Path) -> Result<()> {
     log::debug!("binary path: {:#?}", binary_path);
 
-    if !PathBuf::from(binary_path).exists() {
-        bail!(anyhow::anyhow!(
-            "no release folder found, please run `cargo build --release`"
-        ));
+    if !binary_path.exists() {
+        bail!(
+            "no binary found at {}, please run `rr build` first",
+            binary_path.display()
+        );
     }
     Ok(())
 }
@@ -285,7 +652,7 @@ async fn do_create_release(release_info: ReleaseConfig, tag: &Tag) -> Result<Rel
         .create()
         .tag(tag)
         .target_branch(&release_info.target_branch)
-        .name(&format!("v{}", tag.value()))
+        .name(tag.name())
         .draft(release_info.draft)
         .prerelease(release_info.prerelease)
         .body(release_info.body.unwrap_or_default())
@@ -339,21 +706,63 @@ fn generate_checksum(asset: &Asset) -> Result<String> {
     Ok(checksum)
 }
 
-fn generate_checksum_asset(asset: &Asset, output_path: &PathBuf) -> Result<Asset> {
-    if let Some(checksum) = &asset.checksum {
-        let sha256_file_name = format!("{}.sha256", asset.name);
+/// Signatures of the assets when `release.sign` is set
+async fn signatures(
+    release_info: &ReleaseConfig,
+    assets: &[Asset],
+    extra_assets: &[Asset],
+    checksums: &Asset,
+    dry_run: bool,
+) -> Result<Vec<Asset>> {
+    let Some(config) = &release_info.sign else {
+        return Ok(vec![]);
+    };
+    let assets: Vec<&Asset> = assets.iter().chain(extra_assets).collect();
+    sign::sign(config, &assets, checksums, dry_run).await
+}
 
-        let path = output_path.join(&sha256_file_name);
-        fs::write(&path, format!("{}  {}", checksum, asset.name))?;
+/// The checksums file listing every asset of the release
+fn checksums_asset<'a>(
+    assets: impl Iterator<Item = &'a Asset>,
+    release_info: &ReleaseConfig,
+    tag: &Tag,
+    output_path: &Path,
+) -> Result<Asset> {
+    let config = &release_info.checksum;
+    let name = config.file_name(tag.value(), tag.name())?;
+    let path = output_path.join(&name);
+
+    let files: Vec<(&str, &Path)> = assets
+        .map(|asset| (asset.name.as_str(), asset.path.as_path()))
+        .collect();
+    log::debug!("writing {:?} checksums to {}", config.algorithm, name);
+    checksum::write_manifest(&files, config.algorithm, &path)?;
 
-        let asset = create_asset(&sha256_file_name, path);
-        Ok(asset)
-    } else {
-        bail!(anyhow::anyhow!(
-            "checksum is not available for asset {:#?}",
-            asset
-        ))
-    }
+    Ok(create_asset(name, path))
+}
+
+/// Package of an asset that is not uploaded, pointing at the url it will have
+fn planned_package(release_info: &ReleaseConfig, tag: &Tag, asset: &Asset) -> Package {
+    Package::new(
+        asset.name.to_owned(),
+        None,
+        None,
+        Some(github_client::asset_url(
+            &release_info.owner,
+            &release_info.repo,
+            tag,
+            &asset.name,
+        )),
+        asset.checksum.to_owned().unwrap_or_default(),
+    )
+}
+
+fn uploaded_source(source: Option<&Asset>, uploaded: &[UploadedAsset]) -> Option<Package> {
+    let source = source?;
+    uploaded
+        .iter()
+        .find(|asset| asset.name == source.name)
+        .map(|asset| package_asset(asset, None, None))
 }
 
 fn package_asset(asset: &UploadedAsset, os: Option<&Os>, arch: Option<&Arch>) -> Package {
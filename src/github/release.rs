@@ -1,18 +1,14 @@
-use std::path::PathBuf;
-
-use super::{asset::UploadedAsset, generate_checksum_asset, tag::Tag};
-use crate::{
-    brew::package::Package,
-    github::{asset::Asset, github_client},
-};
-use anyhow::{bail, Result};
+use super::{asset::UploadedAsset, response::release_response::ReleaseAssetResponse, tag::Tag};
+use crate::github::{asset::Asset, github_client};
+use anyhow::{Context, Result};
 
 #[derive(Debug)]
 pub struct Release {
     pub owner: String,
     pub repo: String,
     pub id: u64,
-    pub packages: Vec<Package>,
+    /// Assets already uploaded, only known for a release read from GitHub
+    pub assets: Vec<ReleaseAssetResponse>,
 }
 
 impl Release {
@@ -24,16 +20,26 @@ impl Release {
             id,
             owner: owner.into(),
             repo: repo.into(),
-            packages: vec![],
+            assets: vec![],
         }
     }
 
-    pub async fn upload_assets(
-        &self,
-        assets: Vec<Asset>,
-        tag: &Tag,
-        output_path: &PathBuf,
-    ) -> Result<Vec<UploadedAsset>> {
+    pub fn with_assets(mut self, assets: Vec<ReleaseAssetResponse>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Content of the uploaded asset called `name`
+    pub async fn download_asset(&self, name: &str) -> Result<Vec<u8>> {
+        let asset = self
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .with_context(|| format!("the release has no asset called {}", name))?;
+        github_client::instance().download_asset(&asset.url).await
+    }
+
+    pub async fn upload_assets(&self, assets: Vec<Asset>, tag: &Tag) -> Result<Vec<UploadedAsset>> {
         let mut uploaded = vec![];
         for asset in assets {
             let uploaded_asset = github_client::instance()
@@ -41,26 +47,8 @@ impl Release {
                 .await?;
             log::debug!("Uploaded asset: {:#?}", uploaded_asset);
             uploaded.push(uploaded_asset);
-
-            if let Err(err) = self.upload_checksum_asset(&asset, tag, output_path).await {
-                bail!(err)
-            }
         }
 
         Ok(uploaded)
     }
-
-    async fn upload_checksum_asset(
-        &self,
-        asset: &Asset,
-        tag: &Tag,
-        output_path: &PathBuf,
-    ) -> Result<()> {
-        let checksum_asset = generate_checksum_asset(asset, output_path)?;
-        let ua = github_client::instance()
-            .upload_asset(&checksum_asset, &self.owner, tag, &self.repo, self.id)
-            .await?;
-        log::debug!("Uploaded checksum asset: {:#?}", ua);
-        Ok(())
-    }
 }
@@ -3,4 +3,14 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug)]
 pub struct ReleaseResponse {
     pub id: u64,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAssetResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReleaseAssetResponse {
+    pub name: String,
+    /// API url of the asset, which downloads it with an octet-stream accept
+    /// header, private repositories included
+    pub url: String,
 }
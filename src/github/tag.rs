@@ -2,31 +2,49 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
-    #[serde(rename = "version")]
+    /// Full name of the git tag, e.g. `cli-v1.2.0`
+    #[serde(rename = "tag")]
     pub name: String,
+    /// Version part of the tag, e.g. `1.2.0`
+    pub version: String,
+    #[serde(skip)]
+    prefix: String,
 }
 
 impl Tag {
-    pub fn new(name: impl Into<String>) -> Self {
-        Tag { name: name.into() }
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        let name = name.into();
+        let version = version.into();
+        let prefix = name.strip_suffix(&version).unwrap_or_default().to_string();
+        Tag {
+            name,
+            version,
+            prefix,
+        }
     }
 
+    /// Set the prefix of the semver tags of the project, for tags whose name
+    /// does not end with the version such as `nightly`
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// The version, without the tag prefix
     pub fn value(&self) -> &str {
+        &self.version
+    }
+
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    /// Strip the leading 'v' from the tag name if it exists
-    pub fn strip_v_prefix(&self) -> &str {
-        if self.name.starts_with('v') {
-            self.name.strip_prefix('v').unwrap_or_default()
-        } else {
-            &self.name
-        }
+    /// Everything before the version, e.g. `cli-v`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
     }
 
     pub fn empty() -> Tag {
-        Tag {
-            name: "".to_string(),
-        }
+        Tag::new("", "")
     }
 }
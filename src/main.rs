@@ -1,20 +1,27 @@
 mod brew;
 mod build;
+mod bump;
 mod checksum;
 mod cli;
 mod config;
+mod crates_io;
 mod git;
 mod github;
 mod http;
 mod logger;
+mod metadata;
+mod sbom;
+mod sign;
+mod snapshot;
 mod template;
 
-use tokio::process::Command;
-
-use crate::{cli::Opts, template::Template};
+use crate::cli::{BuildOpts, Command, Opts};
 use anyhow::Result;
+use brew::package::Packages;
 use clap::Parser;
 use config::ReleaserConfig;
+use github::tag::Tag;
+use std::path::Path;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -22,67 +29,288 @@ async fn main() -> Result<()> {
     let opts = Opts::parse();
 
     log::info!("Starting");
-    let config = ReleaserConfig::load(opts.config).await?;
+    // verify checks downloaded files, usually away from any project
+    let verify = matches!(opts.command, Command::Verify { .. });
+    let mut config = ReleaserConfig::load(opts.config).await?;
+    if let Some(project) = opts.command.project() {
+        config.detect(&project.path)?;
+    }
 
-    if !std::fs::metadata(&opts.output).is_ok() {
+    if !verify && std::fs::metadata(&opts.output).is_err() {
         log::info!("Creating directory: {:?}", &opts.output);
         std::fs::create_dir_all(&opts.output)?;
     }
 
-    let build_info = config.build;
+    let prefix = config.git.tag_prefix.to_owned();
+    let prefix = prefix.as_deref();
+    let snapshot = config.snapshot.to_owned();
+    let resolve_tag = |path: &Path| {
+        if opts.snapshot || opts.nightly {
+            snapshot::tag(path, prefix, &snapshot, opts.nightly)
+        } else {
+            git::resolve_tag(path, opts.tag.as_deref(), prefix)
+        }
+    };
+    // snapshots have no tag to check and are never published
+    let validate = !opts.skip_validate && !opts.snapshot && !opts.nightly;
+    let publish_dry_run = opts.dry_run || opts.snapshot;
+    // the formula and crates.io only ever get tagged releases
+    let snapshot_run = opts.snapshot || opts.nightly;
+    // archives are named after the tagged commit, snapshots after HEAD
+    let release_commit = |path: &Path, tag: &Tag| {
+        if snapshot_run {
+            git::head_commit(path)
+        } else {
+            git::tag_commit(path, tag)
+        }
+    };
+    if snapshot_run && matches!(opts.command, Command::Brew { .. } | Command::Publish { .. }) {
+        log::info!("Not publishing a snapshot, running dry");
+    }
+
+    match opts.command {
+        Command::Release {
+            project,
+            build: build_opts,
+        } => {
+            let tag = resolve_tag(&project.path)?;
+            if validate {
+                git::validate::validate(
+                    &project.path,
+                    &tag,
+                    &config.build.binaries,
+                    &config.git.validate,
+                )?;
+            }
+            let failed = build(
+                &config,
+                Some(&tag),
+                &project.path,
+                opts.dry_run,
+                &build_opts,
+            )
+            .await?;
+            if !failed.is_empty() {
+                log::warn!("Skipping the targets that failed to build: {:?}", failed);
+                config.build.ignore.extend(failed);
+            }
+            if opts.nightly {
+                nightly(&mut config, &tag, &project.path, opts.dry_run).await?;
+            }
+            let commit = release_commit(&project.path, &tag)?;
+            let packages = github(
+                &config,
+                &tag,
+                &commit,
+                &project.path,
+                publish_dry_run,
+                &opts.output,
+            )
+            .await?;
+            if snapshot_run {
+                log::info!("Skipping Homebrew and crates.io for a snapshot");
+            } else {
+                brew(&config, packages, tag, opts.dry_run, &opts.output).await?;
+                publish(&config, &project.path, opts.dry_run).await?;
+            }
+        }
+        Command::Build {
+            project,
+            build: build_opts,
+        } => {
+            // untagged commits can be built, their hooks get an empty version
+            let tagged = opts.tag.is_some() || opts.snapshot || opts.nightly;
+            let tag = if tagged {
+                Some(resolve_tag(&project.path)?)
+            } else if !config.build.hooks.is_empty() {
+                git::get_current_tag(&project.path, prefix)
+                    .inspect_err(|err| log::debug!("building without a version: {:#}", err))
+                    .ok()
+            } else {
+                None
+            };
+            build(
+                &config,
+                tag.as_ref(),
+                &project.path,
+                opts.dry_run,
+                &build_opts,
+            )
+            .await?;
+        }
+        Command::Github { project } => {
+            let tag = resolve_tag(&project.path)?;
+            if validate {
+                git::validate::validate(
+                    &project.path,
+                    &tag,
+                    &config.build.binaries,
+                    &config.git.validate,
+                )?;
+            }
+            if opts.nightly {
+                nightly(&mut config, &tag, &project.path, opts.dry_run).await?;
+            }
+            let commit = release_commit(&project.path, &tag)?;
+            github(
+                &config,
+                &tag,
+                &commit,
+                &project.path,
+                publish_dry_run,
+                &opts.output,
+            )
+            .await?;
+        }
+        Command::Brew { project } => {
+            let tag = resolve_tag(&project.path)?;
+            let commit = release_commit(&project.path, &tag)?;
+            let packages =
+                github::published(&config.build, &config.release, &tag, &commit, &opts.output)
+                    .await?;
+            brew(
+                &config,
+                packages,
+                tag,
+                opts.dry_run || snapshot_run,
+                &opts.output,
+            )
+            .await?;
+        }
+        Command::Publish { project } => {
+            publish(&config, &project.path, opts.dry_run || snapshot_run).await?;
+        }
+        Command::Check { project } => {
+            let tag = resolve_tag(&project.path)?;
+            check(&config, &tag, &project.path, validate)?;
+        }
+        Command::Bump {
+            bump,
+            project,
+            push,
+        } => {
+            bump::bump(&project.path, &bump, prefix, push.as_deref(), opts.dry_run)?;
+        }
+        Command::Verify {
+            dir,
+            checksums,
+            algorithm,
+        } => {
+            let checksums = match checksums {
+                Some(checksums) => checksums,
+                None => {
+                    // the checksums file of the given tag, or of any version
+                    let tag = opts
+                        .tag
+                        .as_deref()
+                        .map(|tag| git::resolve_tag(&dir, Some(tag), prefix))
+                        .transpose()?;
+                    checksum::find_manifest(&dir, &config.release.checksum, tag.as_ref())?
+                }
+            };
+            let algorithm = algorithm.unwrap_or(config.release.checksum.algorithm);
+            log::info!("Verifying {} with {:?}", checksums.display(), algorithm);
+            checksum::verify(&checksums, algorithm)?;
+            log::info!("Every file matches its checksum");
+        }
+    }
+
+    Ok(())
+}
 
-    log::info!("Building with {:?}", build_info.tool);
+async fn build(
+    config: &ReleaserConfig,
+    tag: Option<&Tag>,
+    path: &Path,
+    dry_run: bool,
+    build_opts: &BuildOpts,
+) -> Result<Vec<String>> {
+    log::info!("Building with {:?}", config.build.tool);
 
-    build::build(&build_info, opts.path.clone(), opts.dry_run).await?;
+    build::build(
+        &config.build,
+        path.to_path_buf(),
+        tag,
+        dry_run,
+        build_opts.parallelism,
+        build_opts.keep_going,
+    )
+    .await
+}
 
+async fn github(
+    config: &ReleaserConfig,
+    tag: &Tag,
+    commit: &str,
+    path: &Path,
+    dry_run: bool,
+    output: &Path,
+) -> Result<Packages> {
     log::info!("Creating release");
-    let packages = github::release(
-        &build_info,
-        &config.release.clone(),
-        opts.path.clone(),
-        opts.dry_run,
-        &opts.output,
+    github::release(
+        &config.build,
+        &config.release,
+        tag,
+        commit,
+        path.to_path_buf(),
+        dry_run,
+        output,
     )
-    .await?;
+    .await
+}
 
-    if config.brew.is_some() {
+/// Point the release at HEAD as a prerelease, replacing the previous nightly one
+async fn nightly(config: &mut ReleaserConfig, tag: &Tag, path: &Path, dry_run: bool) -> Result<()> {
+    config.release.target_branch = git::head_commit(path)?;
+    config.release.prerelease = true;
+    if dry_run {
+        log::info!("Would replace the {} release", tag.name());
+        return Ok(());
+    }
+    github::delete_release(&config.release, tag).await
+}
+
+async fn brew(
+    config: &ReleaserConfig,
+    packages: Packages,
+    tag: Tag,
+    dry_run: bool,
+    output: &Path,
+) -> Result<()> {
+    if let Some(brew_config) = &config.brew {
         log::info!("Creating brew formula");
         brew::release(
-            config.brew.unwrap(),
-            config.release.clone(),
+            brew_config.to_owned(),
             packages,
-            Template::from(build_info),
-            opts.path.clone(),
-            opts.dry_run,
-            &opts.output,
+            &config.build,
+            tag,
+            dry_run,
+            output,
         )
         .await?;
     }
 
-    if config.crates_io.is_some() && !opts.dry_run {
-        let crates_io = config.crates_io.unwrap();
-        for package in &crates_io.packages {
-            log::info!("Publishing {} to crates.io", package);
-            let mut cmd = Command::new("cargo");
-            cmd.arg("publish").current_dir(opts.path.clone());
-            if crates_io.allow_dirty.unwrap_or(false) {
-                cmd.arg("--allow-dirty");
-            }
-            if crates_io.no_verify.unwrap_or(false) {
-                cmd.arg("--no-verify");
-            }
-            if crates_io.clone().registry.is_some() {
-                cmd.arg("--registry")
-                    .arg(crates_io.clone().registry.unwrap());
-            }
-            if crates_io.clone().index.is_some() {
-                cmd.arg("--index").arg(crates_io.clone().index.unwrap());
-            }
-            cmd.arg("--package").arg(package);
+    Ok(())
+}
 
-            cmd.status().await?;
-        }
+async fn publish(config: &ReleaserConfig, path: &Path, dry_run: bool) -> Result<()> {
+    if let Some(crates_io) = &config.crates_io {
+        crates_io::publish(crates_io, path, dry_run).await?;
     }
 
     Ok(())
 }
+
+fn check(config: &ReleaserConfig, tag: &Tag, path: &Path, validate: bool) -> Result<()> {
+    log::info!("Checking release for {}", path.display());
+
+    log::info!("Current tag: {}", tag.name());
+    if validate {
+        git::validate::validate(path, tag, &config.build.binaries, &config.git.validate)?;
+    }
+
+    github::check(&config.build, path)?;
+    log::info!("Everything is ready for a release");
+
+    Ok(())
+}
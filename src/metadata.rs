@@ -0,0 +1,153 @@
+use crate::build::{binary::Binary, flags::Flags};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The parts of `cargo metadata` used to fill in the config
+#[derive(Debug, Deserialize)]
+pub struct Metadata {
+    pub packages: Vec<Package>,
+    /// Dependency graph, only loaded by [`Metadata::resolve`]
+    pub resolve: Option<Resolve>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+    pub manifest_path: PathBuf,
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Target {
+    pub name: String,
+    pub kind: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Resolve {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Node {
+    pub id: String,
+    pub deps: Vec<NodeDep>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NodeDep {
+    pub pkg: String,
+    pub dep_kinds: Vec<DepKind>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DepKind {
+    /// `dev` or `build`, none for normal dependencies
+    pub kind: Option<String>,
+}
+
+impl NodeDep {
+    /// Whether the dependency ends up in the binaries
+    pub fn is_normal(&self) -> bool {
+        self.dep_kinds.iter().any(|kind| kind.kind.is_none())
+    }
+}
+
+impl Package {
+    fn binaries(&self) -> impl Iterator<Item = &str> {
+        self.targets
+            .iter()
+            .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
+            .map(|target| target.name.as_str())
+    }
+}
+
+impl Metadata {
+    /// Metadata of the workspace members at `base`, without their dependencies
+    pub fn load(base: &Path) -> Result<Metadata> {
+        Metadata::run(base, &["--no-deps".to_string()])
+    }
+
+    /// Metadata of the workspace at `base` and of the dependencies resolved
+    /// for `triple` with the features of `flags`
+    pub fn resolve(base: &Path, triple: &str, flags: &Flags) -> Result<Metadata> {
+        let mut args = vec!["--filter-platform".to_string(), triple.to_string()];
+        if !flags.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(flags.features.join(","));
+        }
+        if flags.no_default_features.unwrap_or_default() {
+            args.push("--no-default-features".to_string());
+        }
+        if flags.locked.unwrap_or_default() {
+            args.push("--locked".to_string());
+        }
+        Metadata::run(base, &args)
+    }
+
+    fn run(base: &Path, args: &[String]) -> Result<Metadata> {
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version", "1"])
+            .args(args)
+            .current_dir(base)
+            .output()
+            .context("failed to run cargo metadata")?;
+        if !output.status.success() {
+            bail!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+
+    /// The package whose manifest is at `base`, none for a virtual workspace
+    pub fn root_package(&self, base: &Path) -> Option<&Package> {
+        let manifest = base.join("Cargo.toml").canonicalize().ok()?;
+        self.packages
+            .iter()
+            .find(|package| package.manifest_path == manifest)
+    }
+
+    /// The package `binary` is built from
+    pub fn package_of(&self, base: &Path, binary: &Binary) -> Option<&Package> {
+        match &binary.package {
+            Some(name) => self.packages.iter().find(|p| &p.name == name),
+            None => self.root_package(base),
+        }
+    }
+
+    /// Binaries of the root package, or of every member of a virtual workspace
+    pub fn binaries(&self, base: &Path) -> Vec<Binary> {
+        match self.root_package(base) {
+            Some(package) => package
+                .binaries()
+                .map(|name| Binary {
+                    name: name.to_string(),
+                    package: None,
+                })
+                .collect(),
+            None => self
+                .packages
+                .iter()
+                .flat_map(|package| {
+                    package.binaries().map(|name| Binary {
+                        name: name.to_string(),
+                        package: Some(package.name.to_owned()),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
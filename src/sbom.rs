@@ -0,0 +1,187 @@
+use crate::{
+    build::{binary::Binary, flags::Flags},
+    metadata::{Metadata, Package},
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+const SPEC_VERSION: &str = "1.5";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Bom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: BomMetadata,
+    components: Vec<Component>,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Serialize)]
+struct BomMetadata {
+    tools: Vec<Tool>,
+    component: Component,
+    properties: Vec<Property>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Property {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Component {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    version: String,
+    purl: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<License>,
+}
+
+#[derive(Serialize)]
+struct License {
+    expression: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Dependency {
+    #[serde(rename = "ref")]
+    reference: String,
+    depends_on: Vec<String>,
+}
+
+impl Component {
+    fn new(package: &Package, kind: &'static str) -> Self {
+        let purl = purl(package);
+        Component {
+            kind,
+            bom_ref: purl.to_owned(),
+            name: package.name.to_owned(),
+            version: package.version.to_owned(),
+            purl,
+            description: package.description.to_owned(),
+            licenses: package
+                .license
+                .iter()
+                .map(|license| License {
+                    expression: license.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn purl(package: &Package) -> String {
+    format!("pkg:cargo/{}@{}", package.name, package.version)
+}
+
+/// Write the CycloneDX SBOM of `binaries` built for `triple` with `flags` to
+/// `output_path`, listing the packages linked into them
+pub fn generate(
+    base: &Path,
+    binaries: &[&Binary],
+    triple: &str,
+    flags: &Flags,
+    output_path: &Path,
+) -> Result<()> {
+    let metadata = Metadata::resolve(base, triple, flags)?;
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .context("cargo metadata did not resolve the dependencies")?;
+    let packages: BTreeMap<&str, &Package> = metadata
+        .packages
+        .iter()
+        .map(|package| (package.id.as_str(), package))
+        .collect();
+    let nodes: BTreeMap<&str, Vec<&str>> = resolve
+        .nodes
+        .iter()
+        .map(|node| {
+            let deps = node
+                .deps
+                .iter()
+                .filter(|dep| dep.is_normal())
+                .map(|dep| dep.pkg.as_str())
+                .collect();
+            (node.id.as_str(), deps)
+        })
+        .collect();
+
+    let mut roots = vec![];
+    for binary in binaries {
+        let package = metadata
+            .package_of(base, binary)
+            .with_context(|| format!("no package found for the binary {}", binary.name))?;
+        if !roots.contains(&package.id.as_str()) {
+            roots.push(package.id.as_str());
+        }
+    }
+
+    // every package reachable from the roots through normal dependencies
+    let mut included = BTreeSet::new();
+    let mut queue = roots.to_owned();
+    while let Some(id) = queue.pop() {
+        if included.insert(id) {
+            queue.extend(nodes.get(id).into_iter().flatten());
+        }
+    }
+
+    let root = packages[roots[0]];
+    let bom = Bom {
+        bom_format: "CycloneDX",
+        spec_version: SPEC_VERSION,
+        version: 1,
+        metadata: BomMetadata {
+            tools: vec![Tool {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            }],
+            component: Component::new(root, "application"),
+            properties: vec![Property {
+                name: "cdx:rustc:target",
+                value: triple.to_string(),
+            }],
+        },
+        components: included
+            .iter()
+            .filter(|id| **id != roots[0])
+            .map(|id| Component::new(packages[id], "library"))
+            .collect(),
+        dependencies: included
+            .iter()
+            .map(|id| Dependency {
+                reference: purl(packages[id]),
+                depends_on: nodes[id]
+                    .iter()
+                    .map(|dep| purl(packages[dep]))
+                    .collect::<BTreeSet<String>>()
+                    .into_iter()
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    fs::write(output_path, serde_json::to_string_pretty(&bom)?)?;
+    Ok(())
+}
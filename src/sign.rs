@@ -0,0 +1,210 @@
+use crate::github::asset::Asset;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+use tempfile::NamedTempFile;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+const ENV_KEY_PREFIX: &str = "env://";
+
+/// Detached signatures of the release assets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignConfig {
+    pub tool: SignTool,
+    #[serde(default)]
+    pub artifacts: SignArtifacts,
+    /// gpg: the key id, the default key otherwise. minisign: the secret key
+    /// file, or `env://NAME` to read it from a variable. cosign: any key
+    /// reference, keyless signing otherwise
+    pub key: Option<String>,
+    /// Variable holding the password of the key
+    pub password_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignTool {
+    Gpg,
+    Minisign,
+    Cosign,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignArtifacts {
+    /// The checksums file only, which covers every other asset
+    #[default]
+    Checksum,
+    /// Every asset and the checksums file
+    All,
+}
+
+impl SignTool {
+    fn extension(&self) -> &str {
+        match self {
+            SignTool::Gpg => "asc",
+            SignTool::Minisign => "minisig",
+            SignTool::Cosign => "sig",
+        }
+    }
+
+    /// Command signing `file` into `signature`, gpg and minisign read the
+    /// password from stdin when `password` is set
+    fn command(&self, key: Option<&str>, password: bool, file: &Path, signature: &Path) -> Command {
+        let mut cmd;
+        match self {
+            SignTool::Gpg => {
+                cmd = Command::new("gpg");
+                cmd.args(["--batch", "--yes"]);
+                if password {
+                    cmd.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+                }
+                if let Some(key) = key {
+                    cmd.args(["--local-user", key]);
+                }
+                cmd.args(["--armor", "--detach-sign", "--output"])
+                    .arg(signature)
+                    .arg(file);
+            }
+            SignTool::Minisign => {
+                cmd = Command::new("minisign");
+                cmd.arg("-S");
+                if let Some(key) = key {
+                    cmd.args(["-s", key]);
+                }
+                cmd.arg("-m").arg(file).arg("-x").arg(signature);
+            }
+            SignTool::Cosign => {
+                cmd = Command::new("cosign");
+                cmd.args(["sign-blob", "--yes"]);
+                if let Some(key) = key {
+                    cmd.args(["--key", key]);
+                }
+                cmd.arg("--output-signature").arg(signature).arg(file);
+            }
+        }
+        cmd
+    }
+}
+
+/// Sign `checksums`, and `assets` when every artifact is signed, returning the
+/// signatures to upload next to them. Nothing is signed in dry run
+pub async fn sign(
+    config: &SignConfig,
+    assets: &[&Asset],
+    checksums: &Asset,
+    dry_run: bool,
+) -> Result<Vec<Asset>> {
+    let mut files = match config.artifacts {
+        SignArtifacts::Checksum => vec![],
+        SignArtifacts::All => assets.to_vec(),
+    };
+    files.push(checksums);
+
+    if dry_run {
+        for file in &files {
+            log::info!("Would sign {} with {:?}", file.name, config.tool);
+        }
+        return Ok(vec![]);
+    }
+
+    let password = match &config.password_env {
+        Some(name) => Some(env::var(name).with_context(|| format!("{} is not set", name))?),
+        None => None,
+    };
+    let key = Key::resolve(config)?;
+
+    let mut signatures = vec![];
+    for file in files {
+        let name = format!("{}.{}", file.name, config.tool.extension());
+        let path = PathBuf::from(format!(
+            "{}.{}",
+            file.path.display(),
+            config.tool.extension()
+        ));
+        log::info!("Signing {} with {:?}", file.name, config.tool);
+
+        let mut cmd = config
+            .tool
+            .command(key.path(), password.is_some(), &file.path, &path);
+        if let (SignTool::Cosign, Some(password)) = (&config.tool, &password) {
+            cmd.env("COSIGN_PASSWORD", password);
+        }
+        let stdin = match config.tool {
+            SignTool::Gpg | SignTool::Minisign => password.as_deref(),
+            SignTool::Cosign => None,
+        };
+        run(cmd, stdin).await?;
+
+        signatures.push(Asset::new(name, path));
+    }
+
+    Ok(signatures)
+}
+
+/// The key handed to the signing tool, a minisign key read from a variable
+/// is written to a private file, with a random name, removed once signing is
+/// done
+enum Key {
+    None,
+    Reference(String),
+    TempFile(NamedTempFile),
+}
+
+impl Key {
+    fn resolve(config: &SignConfig) -> Result<Key> {
+        let Some(key) = &config.key else {
+            return Ok(Key::None);
+        };
+        let variable = key.strip_prefix(ENV_KEY_PREFIX);
+        let (SignTool::Minisign, Some(variable)) = (&config.tool, variable) else {
+            return Ok(Key::Reference(key.to_owned()));
+        };
+
+        let content = env::var(variable).with_context(|| format!("{} is not set", variable))?;
+        // created exclusively and readable by the owner only
+        let mut file = tempfile::Builder::new()
+            .prefix("rr-minisign-")
+            .suffix(".key")
+            .tempfile()?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        Ok(Key::TempFile(file))
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            Key::None => None,
+            Key::Reference(key) => Some(key),
+            Key::TempFile(file) => file.path().to_str(),
+        }
+    }
+}
+
+async fn run(mut cmd: Command, stdin: Option<&str>) -> Result<()> {
+    cmd.stdin(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to run {:?}", cmd.as_std().get_program()))?;
+
+    if let Some(mut pipe) = child.stdin.take() {
+        if let Some(stdin) = stdin {
+            pipe.write_all(format!("{}\n", stdin).as_bytes()).await?;
+        }
+    }
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!(
+            "{:?} failed: {}",
+            cmd.as_std(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
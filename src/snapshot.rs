@@ -0,0 +1,90 @@
+use crate::{
+    bump::{self, Bump},
+    git,
+    github::tag::Tag,
+    template,
+};
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const DEFAULT_VERSION_TEMPLATE: &str = "{{version}}-SNAPSHOT-{{short_commit}}";
+const DEFAULT_NIGHTLY_TAG: &str = "nightly";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Version of the snapshot and nightly builds, rendered with `version`, the
+    /// next patch version, `commit` and `short_commit`
+    #[serde(default = "SnapshotConfig::default_version_template")]
+    pub version_template: String,
+    /// Tag of the rolling nightly release, moved to HEAD on every nightly build
+    #[serde(default = "SnapshotConfig::default_nightly_tag")]
+    pub nightly_tag: String,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig {
+            version_template: SnapshotConfig::default_version_template(),
+            nightly_tag: SnapshotConfig::default_nightly_tag(),
+        }
+    }
+}
+
+impl SnapshotConfig {
+    fn default_version_template() -> String {
+        DEFAULT_VERSION_TEMPLATE.to_owned()
+    }
+
+    fn default_nightly_tag() -> String {
+        DEFAULT_NIGHTLY_TAG.to_owned()
+    }
+}
+
+#[derive(Serialize)]
+struct VersionData<'a> {
+    version: String,
+    commit: &'a str,
+    short_commit: &'a str,
+}
+
+/// Tag of a build of HEAD that has not been tagged: the snapshot version with
+/// the tag prefix, or the nightly tag when `nightly` is set
+pub fn tag(
+    base: &Path,
+    prefix: Option<&str>,
+    config: &SnapshotConfig,
+    nightly: bool,
+) -> Result<Tag> {
+    let next = match git::latest_version(base, prefix)? {
+        Some(version) => Bump::Patch.apply(&version),
+        None => bump::package_version(&base.join("Cargo.toml"))?,
+    };
+    let commit = git::head_commit(base)?;
+
+    let version = template::render(
+        &config.version_template,
+        &VersionData {
+            version: next.to_string(),
+            commit: &commit,
+            short_commit: &commit[..7],
+        },
+    )?;
+    Version::parse(&version).with_context(|| {
+        format!(
+            "snapshot version {} rendered from {} is not a semver version",
+            version, config.version_template
+        )
+    })?;
+
+    let prefix = prefix.unwrap_or("v");
+    let tag = if nightly {
+        Tag::new(&config.nightly_tag, version).with_prefix(prefix)
+    } else {
+        Tag::new(format!("{}{}", prefix, version), version)
+    };
+    log::info!("Snapshot version: {}", tag.value());
+
+    Ok(tag)
+}
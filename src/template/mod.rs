@@ -1,6 +1,8 @@
 use crate::build::Build;
 use anyhow::Result;
-use handlebars::{handlebars_helper, Handlebars};
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde::Serialize;
+use std::fmt::Display;
 
 pub fn handlebars<'hb>() -> Result<Handlebars<'hb>> {
     let mut hb = Handlebars::new();
@@ -18,16 +20,27 @@ pub fn handlebars<'hb>() -> Result<Handlebars<'hb>> {
     Ok(hb)
 }
 
+/// Render a template string from the config, without any html escaping
+pub fn render<T>(template: &str, data: &T) -> Result<String>
+where
+    T: Serialize,
+{
+    let mut hb = handlebars()?;
+    hb.register_escape_fn(no_escape);
+
+    Ok(hb.render_template(template, data)?)
+}
+
 pub enum Template {
     MultiTarget,
     SingleTarget,
 }
 
-impl ToString for Template {
-    fn to_string(&self) -> String {
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Template::MultiTarget => "multi_target".to_string(),
-            Template::SingleTarget => "single_target".to_string(),
+            Template::MultiTarget => write!(f, "multi_target"),
+            Template::SingleTarget => write!(f, "single_target"),
         }
     }
 }